@@ -0,0 +1,20 @@
+mod camera;
+pub use camera::*;
+
+mod projection;
+pub use projection::*;
+
+mod perspective_camera;
+pub use perspective_camera::*;
+
+mod orthographic_camera;
+pub use orthographic_camera::*;
+
+mod fisheye_camera;
+pub use fisheye_camera::*;
+
+mod equirectangular_camera;
+pub use equirectangular_camera::*;
+
+mod cube_map_camera;
+pub use cube_map_camera::*;
@@ -0,0 +1,20 @@
+use crate::vec3::{Vec3, Pt3, self};
+use crate::ray::Ray;
+
+pub trait Camera where Self: Send + Sync {
+    // Ray going through the point of the image at u and v in range <0, 1>, u going right and v going down the image.
+    // None for parts of the image the camera doesn't see, like the corners around the circle of a fisheye lens.
+    fn ray(&self, u: f32, v: f32) -> Option<Ray>;
+}
+
+// Right and up vectors of the image and the forward vector going from the target back to the camera.
+// Up is the direction in which v grows.
+pub fn camera_axes(look_from: Pt3, look_at: Pt3) -> (Vec3, Vec3, Vec3) {
+    let forward = (look_from - look_at).normalized();
+    // Can't look straight up or down becuase then where should left and up vectors be.
+    // cross(x, x) creates the zero vector so it can't work.
+    assert!(forward != vec3::UP && forward != vec3::DOWN);
+    let right = Vec3::cross(vec3::UP, forward).normalized();
+    let up = Vec3::cross(forward, right);
+    (right, up, forward)
+}
@@ -0,0 +1,85 @@
+use crate::vec3::{Vec3, Pt3};
+use crate::ray::Ray;
+
+use super::Camera;
+
+// Renders the six faces of a cube map around the camera next to each other, for environment baking and VR.
+// The faces are in the order +x, -x, +y, -y, +z, -z and each covers 90 degrees, so the image should be six times
+// as wide as it's high. They are aligned with the world axes and oriented like the faces of an OpenGL cube map.
+pub struct CubeMapCamera {
+    pub origin: Pt3,
+    pub pixel_spread: f32
+}
+
+impl CubeMapCamera {
+    pub fn new(look_from: Pt3, image_height: usize) -> Self {
+        Self { origin: look_from, pixel_spread: 2.0 / image_height as f32 }
+    }
+}
+
+impl Camera for CubeMapCamera {
+    fn ray(&self, u: f32, v: f32) -> Option<Ray> {
+        let face = usize::min((u * 6.0) as usize, 5);
+        // Position on the face in range <-1, 1> going right and down.
+        let s = 2.0 * (u * 6.0 - face as f32) - 1.0;
+        let t = 2.0 * v - 1.0;
+        let direction = match face {
+            0 => Vec3::new(1.0, -t, -s),
+            1 => Vec3::new(-1.0, -t, s),
+            2 => Vec3::new(s, 1.0, t),
+            3 => Vec3::new(s, -1.0, -t),
+            4 => Vec3::new(s, -t, 1.0),
+            _ => Vec3::new(-s, -t, -1.0)
+        };
+        Some(Ray { spread: self.pixel_spread, ..Ray::new(self.origin, direction) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AXES: [Vec3; 6] = [
+        Vec3 { x: 1.0, y: 0.0, z: 0.0 }, Vec3 { x: -1.0, y: 0.0, z: 0.0 },
+        Vec3 { x: 0.0, y: 1.0, z: 0.0 }, Vec3 { x: 0.0, y: -1.0, z: 0.0 },
+        Vec3 { x: 0.0, y: 0.0, z: 1.0 }, Vec3 { x: 0.0, y: 0.0, z: -1.0 }
+    ];
+
+    fn direction(camera: &CubeMapCamera, u: f32, v: f32) -> Vec3 {
+        camera.ray(u, v).unwrap().direction.normalized()
+    }
+
+    #[test]
+    fn face_centers_and_edges() {
+        let camera = CubeMapCamera::new(Pt3::all(0.0), 64);
+        for (face, axis) in AXES.iter().enumerate() {
+            let center = (face as f32 + 0.5) / 6.0;
+            assert!((direction(&camera, center, 0.5) - *axis).length() < 1e-5, "face {}", face);
+            // The middles of the edges are 45 degrees away from the axis.
+            for (u, v) in [(face as f32 / 6.0, 0.5), ((face as f32 + 0.999) / 6.0, 0.5), (center, 0.0), (center, 0.999)] {
+                let cos = Vec3::dot(direction(&camera, u, v), *axis);
+                assert!((cos - f32::sqrt(0.5)).abs() < 1e-2, "face {} at ({}, {})", face, u, v);
+            }
+        }
+        // Going right from +z continues on +x.
+        let right_of_z = direction(&camera, 5.0 / 6.0 - 1e-6, 0.3);
+        let left_of_x = direction(&camera, 0.0, 0.3);
+        assert!((right_of_z - left_of_x).length() < 1e-3);
+    }
+
+    #[test]
+    fn pixels_stay_on_their_face() {
+        let height = 16;
+        let width = 6 * height;
+        let camera = CubeMapCamera::new(Pt3::all(0.0), height);
+        for x in 0..width {
+            for r in [0.0, 0.5, 0.999] {
+                let u = (x as f32 + r) / width as f32;
+                let direction = direction(&camera, u, 0.5);
+                let axis = AXES[x / height];
+                // The ray points more along the face's axis than along any other.
+                assert!(AXES.iter().all(|other| Vec3::dot(direction, axis) >= Vec3::dot(direction, *other)), "pixel {} at {}", x, r);
+            }
+        }
+    }
+}
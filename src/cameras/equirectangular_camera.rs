@@ -0,0 +1,84 @@
+use std::f32::consts::{PI, TAU};
+
+use crate::vec3::{Vec3, Pt3};
+use crate::ray::Ray;
+
+use super::{Camera, camera_axes};
+
+// 360 degree panorama for VR and environment maps. The horizontal angle around the camera goes along the width of
+// the image and the vertical angle along the height, so the image should be twice as wide as it's high.
+// The view direction is in the center of the image.
+pub struct EquirectangularCamera {
+    pub origin: Pt3,
+    pub right: Vec3,
+    pub up: Vec3,
+    pub forward: Vec3,
+    pub pixel_spread: f32
+}
+
+impl EquirectangularCamera {
+    pub fn new(look_from: Pt3, look_at: Pt3, image_height: usize) -> Self {
+        let (right, up, forward) = camera_axes(look_from, look_at);
+        Self { origin: look_from, right, up, forward, pixel_spread: PI / image_height as f32 }
+    }
+}
+
+impl Camera for EquirectangularCamera {
+    fn ray(&self, u: f32, v: f32) -> Option<Ray> {
+        let longitude = (u - 0.5) * TAU;
+        let latitude = (v - 0.5) * PI;
+        let (sin_longitude, cos_longitude) = longitude.sin_cos();
+        let (sin_latitude, cos_latitude) = latitude.sin_cos();
+        let direction = cos_latitude * (cos_longitude * -self.forward + sin_longitude * self.right) + sin_latitude * self.up;
+        Some(Ray { spread: self.pixel_spread, ..Ray::new(self.origin, direction) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera() -> EquirectangularCamera {
+        EquirectangularCamera::new(Pt3::new(1.0, 2.0, 3.0), Pt3::new(4.0, 2.0, -1.0), 100)
+    }
+
+    fn direction(camera: &EquirectangularCamera, u: f32, v: f32) -> Vec3 {
+        let ray = camera.ray(u, v).unwrap();
+        assert_eq!(ray.origin, camera.origin);
+        ray.direction
+    }
+
+    #[test]
+    fn directions() {
+        let camera = camera();
+        let close = |a: Vec3, b: Vec3| (a - b).length() < 1e-5;
+        // The center looks at the target, the middle row goes around the horizon and the edges meet behind the camera.
+        assert!(close(direction(&camera, 0.5, 0.5), -camera.forward));
+        assert!(close(direction(&camera, 0.75, 0.5), camera.right));
+        assert!(close(direction(&camera, 0.25, 0.5), -camera.right));
+        assert!(close(direction(&camera, 0.0, 0.5), camera.forward));
+        assert!(close(direction(&camera, 1.0, 0.5), camera.forward));
+        // The top and bottom rows are the poles.
+        assert!(close(direction(&camera, 0.3, 1.0), camera.up));
+        assert!(close(direction(&camera, 0.8, 0.0), -camera.up));
+    }
+
+    #[test]
+    fn mapping() {
+        let camera = camera();
+        // Equal steps in the image are equal angles, both horizontally and vertically.
+        let angle = |a: Vec3, b: Vec3| Vec3::dot(a, b).clamp(-1.0, 1.0).acos();
+        for i in 0..10 {
+            let (u, v) = (i as f32 / 10.0, 0.05 + i as f32 / 11.0);
+            let d = direction(&camera, u, v);
+            assert!((d.length() - 1.0).abs() < 1e-5);
+            let below = direction(&camera, u, v + 0.01);
+            assert!((angle(d, below) - 0.01 * PI).abs() < 1e-3);
+            let latitude = (v - 0.5) * PI;
+            let beside = direction(&camera, u + 0.01, v);
+            assert!((angle(d, beside) - 2.0 * f32::asin(latitude.cos() * f32::sin(0.01 * PI))).abs() < 1e-3);
+        }
+        // A pixel covers the same angle vertically.
+        assert!((camera.pixel_spread - PI / 100.0).abs() < 1e-7);
+    }
+}
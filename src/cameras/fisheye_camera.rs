@@ -0,0 +1,91 @@
+use std::f32::consts::TAU;
+
+use crate::vec3::{Vec3, Pt3};
+use crate::ray::Ray;
+
+use super::{Camera, camera_axes};
+
+// How the angle from the center of the view is mapped to the distance from the center of the image circle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FisheyeMapping {
+    // The distance grows linearly with the angle.
+    Equidistant,
+    // Equal areas of the image cover equal solid angles, so the edges are less squashed.
+    Equisolid
+}
+
+// Ultra wide angle lens. The whole view is in a circle fitting the shorter side of the image and the rest is black.
+pub struct FisheyeCamera {
+    pub origin: Pt3,
+    pub right: Vec3,
+    pub up: Vec3,
+    pub forward: Vec3,
+    // Angle covered by the diameter of the circle. Can be more than 180 degrees to see behind the camera.
+    pub fov: f32,
+    pub mapping: FisheyeMapping,
+    pub aspect_ratio: f32,
+    pub pixel_spread: f32
+}
+
+impl FisheyeCamera {
+    pub fn new(look_from: Pt3, look_at: Pt3, fov: f32, mapping: FisheyeMapping, aspect_ratio: f32, image_height: usize) -> Self {
+        let (right, up, forward) = camera_axes(look_from, look_at);
+        let fov = fov.clamp(0.0, TAU);
+        let circle_pixels = image_height as f32 * f32::min(aspect_ratio, 1.0);
+        Self { origin: look_from, right, up, forward, fov, mapping, aspect_ratio, pixel_spread: fov / circle_pixels }
+    }
+}
+
+impl Camera for FisheyeCamera {
+    fn ray(&self, u: f32, v: f32) -> Option<Ray> {
+        // Position relative to the center of the image where the edge of the circle is at 1.
+        let scale = 2.0 / f32::min(self.aspect_ratio, 1.0);
+        let x = (u - 0.5) * scale * self.aspect_ratio;
+        let y = (v - 0.5) * scale;
+        let radius = f32::sqrt(x * x + y * y);
+        if radius > 1.0 {
+            return None;
+        }
+
+        // Angle between the ray and the view direction.
+        let theta = match self.mapping {
+            FisheyeMapping::Equidistant => radius * self.fov / 2.0,
+            FisheyeMapping::Equisolid => 2.0 * f32::asin(radius * f32::sin(self.fov / 4.0))
+        };
+        let (sin, cos) = theta.sin_cos();
+        let sideways = if radius > 0.0 { (x * self.right + y * self.up) / radius } else { Vec3::all(0.0) };
+        let direction = cos * -self.forward + sin * sideways;
+        Some(Ray { spread: self.pixel_spread, ..Ray::new(self.origin, direction) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera(aspect_ratio: f32) -> FisheyeCamera {
+        FisheyeCamera::new(Pt3::all(0.0), Pt3::new(0.0, 0.0, -1.0), std::f32::consts::PI, FisheyeMapping::Equidistant, aspect_ratio, 100)
+    }
+
+    #[test]
+    fn corners_are_outside_of_the_circle() {
+        let camera = camera(1.0);
+        for (u, v) in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0), (0.05, 0.05)] {
+            assert!(camera.ray(u, v).is_none(), "({}, {})", u, v);
+        }
+        let center = camera.ray(0.5, 0.5).unwrap().direction.normalized();
+        assert!((center - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-5);
+        // With a 180 degree view the edge of the circle looks sideways.
+        let edge = camera.ray(1.0, 0.5).unwrap().direction.normalized();
+        assert!(edge.z.abs() < 1e-5 && (edge.x.abs() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn circle_fits_the_shorter_side() {
+        let camera = camera(2.0);
+        assert!(camera.ray(0.1, 0.5).is_none());
+        assert!(camera.ray(0.5, 0.0).is_some());
+        let edge = camera.ray(0.25, 0.5).unwrap().direction.normalized();
+        assert!(edge.z.abs() < 1e-5);
+    }
+}
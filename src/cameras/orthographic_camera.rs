@@ -0,0 +1,62 @@
+use crate::vec3::{Vec3, Pt3};
+use crate::ray::Ray;
+
+use super::{Camera, camera_axes};
+
+// All rays are parallel so objects keep their size no matter how far they are. Used for technical and architectural
+// views where lines that are parallel in the scene should stay parallel in the image.
+pub struct OrthographicCamera {
+    pub view_plane_lower_left_corner: Pt3,
+    pub horizontal: Vec3,
+    pub vertical: Vec3,
+    pub forward: Vec3,
+    // Size of a pixel. The rays don't spread so the cone stays this wide.
+    pub pixel_width: f32
+}
+
+impl OrthographicCamera {
+    // The view height is the size of the visible area in world units.
+    pub fn new(look_from: Pt3, look_at: Pt3, view_height: f32, aspect_ratio: f32, image_height: usize) -> Self {
+        let (right, up, forward) = camera_axes(look_from, look_at);
+        let horizontal = aspect_ratio * view_height * right;
+        let vertical = view_height * up;
+        // The rays start at the camera so objects behind it aren't visible.
+        let view_plane_lower_left_corner = look_from - (horizontal / 2.0) - (vertical / 2.0);
+        Self { view_plane_lower_left_corner, horizontal, vertical, forward, pixel_width: view_height / image_height as f32 }
+    }
+}
+
+impl Camera for OrthographicCamera {
+    fn ray(&self, u: f32, v: f32) -> Option<Ray> {
+        let origin = self.view_plane_lower_left_corner + (u * self.horizontal) + (v * self.vertical);
+        Some(Ray { cone_width: self.pixel_width, ..Ray::new(origin, -self.forward) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parallel_rays() {
+        let (look_from, look_at) = (Pt3::new(1.0, 2.0, 3.0), Pt3::new(4.0, 2.0, -1.0));
+        let camera = OrthographicCamera::new(look_from, look_at, 4.0, 2.0, 100);
+        let view_direction = (look_at - look_from).normalized();
+        let (right, up, _) = camera_axes(look_from, look_at);
+
+        let ray_at = |u: f32, v: f32| camera.ray(u, v).unwrap();
+        for (u, v) in [(0.0, 0.0), (0.5, 0.5), (1.0, 0.25), (0.3, 1.0)] {
+            let ray = ray_at(u, v);
+            assert!((ray.direction - view_direction).length() < 1e-5);
+            assert_eq!(ray.spread, 0.0);
+            assert!((ray.cone_width - 0.04).abs() < 1e-7);
+        }
+
+        // The rays start on a plane through the camera, as wide as the aspect ratio times the view height.
+        assert!((ray_at(0.5, 0.5).origin - look_from).length() < 1e-5);
+        assert!((ray_at(1.0, 0.5).origin - (look_from + 4.0 * right)).length() < 1e-5);
+        assert!((ray_at(0.0, 0.5).origin - (look_from - 4.0 * right)).length() < 1e-5);
+        assert!((ray_at(0.5, 1.0).origin - (look_from + 2.0 * up)).length() < 1e-5);
+        assert!((ray_at(0.25, 0.0).origin - (look_from - 2.0 * right - 2.0 * up)).length() < 1e-5);
+    }
+}
@@ -1,7 +1,10 @@
-use crate::vec3::{Vec3, self};
+use crate::vec3::Vec3;
 use crate::ray::Ray;
 
-pub struct Camera {
+use super::{Camera, camera_axes};
+
+// Thin lens camera. Points at the focus distance are sharp and the rest gets blurrier the bigger the aperture is.
+pub struct PerspectiveCamera {
     pub origin: Vec3,
     pub view_plane_lower_left_corner: Vec3,
     pub horizontal: Vec3,
@@ -9,35 +12,35 @@ pub struct Camera {
     pub right: Vec3,
     pub up: Vec3,
     pub forward: Vec3,
-    pub lens_radius: f32
+    pub lens_radius: f32,
+    // Angle covered by a single pixel.
+    pub pixel_spread: f32
 }
 
-impl Camera {
-    pub fn new(look_from: Vec3, look_at: Vec3, vertical_fov: f32, aspect_ratio: f32, aperture: f32, focus_distance: f32) -> Camera {
+impl PerspectiveCamera {
+    pub fn new(look_from: Vec3, look_at: Vec3, vertical_fov: f32, aspect_ratio: f32, aperture: f32, focus_distance: f32, image_height: usize) -> PerspectiveCamera {
         let viewport_height: f32 = 2.0 * f32::tan(vertical_fov / 2.0);
         let viewport_width: f32 = aspect_ratio * viewport_height;
 
-        let forward = (look_from - look_at).normalized();
-        // Can't look straight up or down becuase then where should left and up vectors be.
-        // cross(x, x) creates the zero vector so it can't work.
-        assert!(forward != vec3::UP && forward != vec3::DOWN);
-        let right = Vec3::cross(vec3::UP, forward).normalized();
-        let up = Vec3::cross(forward, right);
+        let (right, up, forward) = camera_axes(look_from, look_at);
 
         let origin = look_from;
         let horizontal = focus_distance * viewport_width * right;
         let vertical = focus_distance * viewport_height * up;
         let view_plane_lower_left_corner = origin - (horizontal / 2.0) - (vertical / 2.0) - (focus_distance * forward);
+        let pixel_spread = viewport_height / image_height as f32;
 
-        Self {origin, view_plane_lower_left_corner, vertical, horizontal, lens_radius: aperture / 2.0, forward, right, up }
+        Self {origin, view_plane_lower_left_corner, vertical, horizontal, lens_radius: aperture / 2.0, forward, right, up, pixel_spread }
     }
+}
 
-    pub fn ray(&self, u: f32, v: f32) -> Ray {
+impl Camera for PerspectiveCamera {
+    fn ray(&self, u: f32, v: f32) -> Option<Ray> {
         let point_on_lens = self.lens_radius * Vec3::random_in_unit_disk_on_z_plane();
         let offset = (self.right * point_on_lens.x) + (self.up * point_on_lens.y);
         let origin = self.origin + offset;
         let point_on_view_plane = (self.view_plane_lower_left_corner + (u * self.horizontal)) + (v * self.vertical);
         let direction = point_on_view_plane - origin;
-        Ray::new(origin, direction)
+        Some(Ray { spread: self.pixel_spread, ..Ray::new(origin, direction) })
     }
-}
\ No newline at end of file
+}
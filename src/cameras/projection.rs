@@ -0,0 +1,27 @@
+use super::FisheyeMapping;
+
+// Which kind of camera renders the scene. All of them are placed at the look from point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    // Thin lens camera using the field of view, aperture and focus distance of the scene.
+    Perspective,
+    // Parallel rays covering an area of this height in world units.
+    Orthographic { view_height: f32 },
+    // The field of view covered by the image circle in radians.
+    Fisheye { fov: f32, mapping: FisheyeMapping },
+    // Needs an image twice as wide as it's high.
+    Equirectangular,
+    // Needs an image six times as wide as it's high. Ignores the look at point.
+    CubeMap
+}
+
+impl Projection {
+    // Panoramas only work with one shape of the image. Returns how many times wider than high it has to be.
+    pub fn required_aspect_ratio(&self) -> Option<usize> {
+        match self {
+            Projection::Equirectangular => Some(2),
+            Projection::CubeMap => Some(6),
+            _ => None
+        }
+    }
+}
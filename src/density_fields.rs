@@ -0,0 +1,11 @@
+mod density_field;
+pub use density_field::*;
+
+mod noise_density;
+pub use noise_density::*;
+
+mod voxel_grid;
+pub use voxel_grid::*;
+
+mod voxel_volume;
+pub use voxel_volume::*;
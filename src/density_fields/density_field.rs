@@ -0,0 +1,16 @@
+use crate::{vec3::Pt3, ray::Ray};
+
+// Density of particles in a medium that changes from point to point.
+pub trait DensityField where Self: Send + Sync {
+    fn density(&self, point: Pt3) -> f32;
+
+    // Upper bound of the density anywhere in the field. Distances are sampled as if the whole medium
+    // had this density and then the extra collisions are rejected, so a tight bound makes rendering faster.
+    fn max_density(&self) -> f32;
+
+    // Splits the part of the ray between t_min and t_max into segments, each with its own upper bound of the density
+    // as (start, end, bound). Fields with empty or thin regions can return tighter bounds than the max density there.
+    fn majorants(&self, _ray: &Ray, t_min: f32, t_max: f32) -> Vec<(f32, f32, f32)> {
+        vec![(t_min, t_max, self.max_density())]
+    }
+}
@@ -0,0 +1,31 @@
+use crate::{perlin::{Perlin, Turbulence}, vec3::Pt3};
+
+use super::DensityField;
+
+// Wispy smoke or clouds made from fractal noise.
+pub struct NoiseDensity {
+    pub perlin: Perlin,
+    // Density where the noise is the highest.
+    pub density: f32,
+    // Number of noise cells per unit of distance.
+    pub frequency: f32,
+    pub turbulence: Turbulence
+}
+
+impl NoiseDensity {
+    pub fn new(density: f32, frequency: f32) -> Self {
+        Self { perlin: Perlin::new(), density, frequency, turbulence: Turbulence::default() }
+    }
+}
+
+impl DensityField for NoiseDensity {
+    fn density(&self, point: Pt3) -> f32 {
+        // Mapping from <-1, 1> to <0, 1>. The noise can go slightly outside of the range so it's clamped to keep the bound.
+        let noise = 0.5 * (1.0 + self.perlin.fbm(self.frequency * point, self.turbulence));
+        self.density * noise.clamp(0.0, 1.0)
+    }
+
+    fn max_density(&self) -> f32 {
+        self.density
+    }
+}
@@ -0,0 +1,241 @@
+use crate::{aabb::Aabb, vec3::Pt3, ray::Ray};
+
+use super::DensityField;
+
+// Number of voxels along each side of a cell of the majorant grid.
+const MAJORANT_CELL_SIZE: usize = 8;
+
+// Values stored in a 3D grid of voxels stretched over a box, interpolated trilinearly between the voxel centers.
+// Outside of the box the value is 0. The box is axis aligned and in the same space as the medium using the grid,
+// so it only moves and stretches the grid, it isn't a transform.
+pub struct VoxelGrid {
+    width: usize,
+    height: usize,
+    depth: usize,
+    // Going along x first, then y and then z.
+    values: Vec<f32>,
+    max: f32,
+    // Coarse grid with the max value of the voxels each cell can interpolate between. Delta tracking uses it
+    // to skip empty space and to sample the thin edges of smoke with a much lower bound than the densest voxel.
+    majorants: Vec<f32>,
+    majorant_size: [usize; 3],
+    // The box the voxels are stretched over.
+    pub bounds: Aabb
+}
+
+impl VoxelGrid {
+    pub fn new(width: usize, height: usize, depth: usize, values: Vec<f32>, bounds: Aabb) -> Self {
+        assert_eq!(values.len(), width * height * depth, "the number of values doesn't match the size of the grid");
+        let max = values.iter().fold(0.0f32, |max, &value| max.max(value));
+        let mut grid = Self { width, height, depth, values, max, majorants: Vec::new(), majorant_size: [0; 3], bounds };
+        grid.build_majorants();
+        grid
+    }
+
+    pub fn value(&self, x: usize, y: usize, z: usize) -> f32 {
+        self.values[(z * self.height + y) * self.width + x]
+    }
+
+    fn size(&self) -> [usize; 3] {
+        [self.width, self.height, self.depth]
+    }
+
+    fn build_majorants(&mut self) {
+        let size = self.size();
+        let majorant_size = size.map(|n| n.div_ceil(MAJORANT_CELL_SIZE));
+        let mut majorants = Vec::with_capacity(majorant_size.iter().product());
+        for cz in 0..majorant_size[2] {
+            for cy in 0..majorant_size[1] {
+                for cx in 0..majorant_size[0] {
+                    // Points in the cell are interpolated from voxels up to one voxel outside of it.
+                    let range = |c: usize, n: usize| (c * MAJORANT_CELL_SIZE).saturating_sub(1)..usize::min((c + 1) * MAJORANT_CELL_SIZE + 1, n);
+                    let mut max = 0.0f32;
+                    for z in range(cz, size[2]) {
+                        for y in range(cy, size[1]) {
+                            for x in range(cx, size[0]) {
+                                max = max.max(self.value(x, y, z));
+                            }
+                        }
+                    }
+                    majorants.push(max);
+                }
+            }
+        }
+        self.majorants = majorants;
+        self.majorant_size = majorant_size;
+    }
+
+    // Position in the grid where voxel i covers <i, i + 1>.
+    fn grid_position(&self, point: Pt3) -> [f32; 3] {
+        let size = self.size();
+        [0, 1, 2].map(|axis| (point[axis] - self.bounds.min[axis]) / (self.bounds.max[axis] - self.bounds.min[axis]) * size[axis] as f32)
+    }
+}
+
+impl DensityField for VoxelGrid {
+    fn density(&self, point: Pt3) -> f32 {
+        let size = self.size();
+        let position = self.grid_position(point);
+        let mut lower = [0; 3];
+        let mut upper = [0; 3];
+        let mut weights = [0.0; 3];
+        for axis in 0..3 {
+            if !(0.0..=size[axis] as f32).contains(&position[axis]) {
+                return 0.0;
+            }
+            // Voxel centers are at half integers. Near the sides the outermost voxel is used as is.
+            let x = (position[axis] - 0.5).clamp(0.0, (size[axis] - 1) as f32);
+            lower[axis] = x.floor() as usize;
+            upper[axis] = usize::min(lower[axis] + 1, size[axis] - 1);
+            weights[axis] = x - lower[axis] as f32;
+        }
+
+        let mut value = 0.0;
+        for corner in 0..8 {
+            let mut weight = 1.0;
+            let mut index = [0; 3];
+            for axis in 0..3 {
+                let is_upper = corner & (1 << axis) != 0;
+                index[axis] = if is_upper { upper[axis] } else { lower[axis] };
+                weight *= if is_upper { weights[axis] } else { 1.0 - weights[axis] };
+            }
+            value += weight * self.value(index[0], index[1], index[2]);
+        }
+        value
+    }
+
+    fn max_density(&self) -> f32 {
+        self.max
+    }
+
+    // Walks through the cells of the majorant grid that the ray passes (Amanatides and Woo).
+    fn majorants(&self, ray: &Ray, t_min: f32, t_max: f32) -> Vec<(f32, f32, f32)> {
+        let mut segments = Vec::new();
+        // Clipping the ray to the box of the grid first.
+        let (mut t_start, mut t_end) = (t_min, t_max);
+        for axis in 0..3 {
+            let inverse = 1.0 / ray.direction[axis];
+            let mut t0 = (self.bounds.min[axis] - ray.origin[axis]) * inverse;
+            let mut t1 = (self.bounds.max[axis] - ray.origin[axis]) * inverse;
+            if inverse < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            // NaN when the ray is parallel to the side and starts on it, in which case the axis doesn't limit anything.
+            if !t0.is_nan() {
+                t_start = t_start.max(t0);
+            }
+            if !t1.is_nan() {
+                t_end = t_end.min(t1);
+            }
+        }
+        if t_start >= t_end {
+            return segments;
+        }
+
+        // Grid of cells where each cell has size 1. The last cell along an axis can be cut off by the side of the grid
+        // so the scale has to come from the voxels and not from the number of cells.
+        let cells = self.majorant_size;
+        let size = self.size();
+        let to_cells = |axis: usize| size[axis] as f32 / (MAJORANT_CELL_SIZE as f32 * (self.bounds.max[axis] - self.bounds.min[axis]));
+        let start = ray.at(t_start);
+        let mut cell = [0i64; 3];
+        let mut step = [0i64; 3];
+        let mut t_next = [f32::INFINITY; 3];
+        let mut t_delta = [f32::INFINITY; 3];
+        for axis in 0..3 {
+            let position = (start[axis] - self.bounds.min[axis]) * to_cells(axis);
+            cell[axis] = (position.floor() as i64).clamp(0, cells[axis] as i64 - 1);
+            let direction = ray.direction[axis] * to_cells(axis);
+            if direction > 0.0 {
+                step[axis] = 1;
+                t_delta[axis] = 1.0 / direction;
+                t_next[axis] = t_start + (cell[axis] as f32 + 1.0 - position) / direction;
+            } else if direction < 0.0 {
+                step[axis] = -1;
+                t_delta[axis] = -1.0 / direction;
+                t_next[axis] = t_start + (cell[axis] as f32 - position) / direction;
+            }
+        }
+
+        let mut t = t_start;
+        loop {
+            let axis = (0..3).min_by(|&a, &b| t_next[a].total_cmp(&t_next[b])).unwrap();
+            let t_exit = f32::min(t_next[axis], t_end);
+            let index = (cell[2] as usize * cells[1] + cell[1] as usize) * cells[0] + cell[0] as usize;
+            if t_exit > t {
+                segments.push((t, t_exit, self.majorants[index]));
+            }
+            if t_exit >= t_end {
+                return segments;
+            }
+            t = t_exit;
+            cell[axis] += step[axis];
+            if cell[axis] < 0 || cell[axis] >= cells[axis] as i64 {
+                return segments;
+            }
+            t_next[axis] += t_delta[axis];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+    use crate::vec3::Vec3;
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn trilinear_interpolation() {
+        // 2x2x2 voxels over a box of size 2 so the voxel centers are at 0.5 and 1.5.
+        let values = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0];
+        let grid = VoxelGrid::new(2, 2, 2, values, Aabb::new(Pt3::all(0.0), Pt3::all(2.0)));
+        for z in 0..2 {
+            for y in 0..2 {
+                for x in 0..2 {
+                    let center = Pt3::new(x as f32 + 0.5, y as f32 + 0.5, z as f32 + 0.5);
+                    assert!(approx(grid.density(center), grid.value(x, y, z)));
+                }
+            }
+        }
+
+        // Halfway between two centers along each axis.
+        assert!(approx(grid.density(Pt3::new(1.0, 0.5, 0.5)), 1.5));
+        assert!(approx(grid.density(Pt3::new(0.5, 1.0, 0.5)), 2.0));
+        assert!(approx(grid.density(Pt3::new(0.5, 0.5, 1.0)), 3.0));
+        assert!(approx(grid.density(Pt3::all(1.0)), 4.5));
+        // Between the outermost centers and the sides the value doesn't change.
+        assert!(approx(grid.density(Pt3::new(0.1, 0.2, 0.3)), 1.0));
+        assert_eq!(grid.density(Pt3::new(-0.1, 1.0, 1.0)), 0.0);
+        assert_eq!(grid.density(Pt3::new(1.0, 2.1, 1.0)), 0.0);
+    }
+
+    #[test]
+    fn density_is_below_the_majorants() {
+        // Mostly empty with a few dense blobs so the majorants differ a lot between cells.
+        let (width, height, depth) = (20, 13, 17);
+        let mut rng = rand::thread_rng();
+        let values = (0..width * height * depth).map(|_| if rng.gen::<f32>() < 0.1 { rng.gen_range(0.0..5.0) } else { 0.0 }).collect();
+        let grid = VoxelGrid::new(width, height, depth, values, Aabb::new(Pt3::new(-1.0, 0.0, 2.0), Pt3::new(3.0, 1.5, 4.0)));
+
+        for _ in 0..500 {
+            let origin = Pt3::new(rng.gen_range(-3.0..5.0), rng.gen_range(-2.0..3.5), rng.gen_range(0.0..6.0));
+            let ray = Ray::new(origin, Vec3::random_unit() * rng.gen_range(0.5..2.0));
+            let segments = grid.majorants(&ray, 0.0, 20.0);
+            for window in segments.windows(2) {
+                assert!(approx(window[0].1, window[1].0), "segments have a gap");
+            }
+            for (start, end, majorant) in segments {
+                assert!(start < end);
+                for i in 0..=20 {
+                    let t = start + (end - start) * i as f32 / 20.0;
+                    let density = grid.density(ray.at(t));
+                    assert!(density <= majorant + 1e-4, "density {} at t = {} is above the majorant {}", density, t, majorant);
+                }
+            }
+        }
+    }
+}
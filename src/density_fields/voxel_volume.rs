@@ -0,0 +1,197 @@
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::aabb::Aabb;
+
+use super::VoxelGrid;
+
+#[derive(Debug)]
+pub struct VolumeLoadError {
+    pub path: PathBuf,
+    pub cause: Error
+}
+
+impl std::fmt::Display for VolumeLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to load volume {}: {}", self.path.display(), self.cause)
+    }
+}
+
+impl std::error::Error for VolumeLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.cause)
+    }
+}
+
+// Dense voxel data exported from a simulation. Stored in a simple binary format, all numbers little endian:
+//
+//   4 bytes   magic "VXGR"
+//   u32       version, currently 1
+//   u32 x 3   width, height and depth in voxels
+//   u32       channels present, 1 = density (required), 2 = temperature in kelvin, 4 = emission strength
+//   f32 ...   width * height * depth values for each present channel in the order density, temperature, emission,
+//             going along x first, then y and then z
+//
+// Converting from other formats like VDB only needs the dense values written out in this order.
+pub struct VoxelVolume {
+    pub density: Arc<VoxelGrid>,
+    pub temperature: Option<Arc<VoxelGrid>>,
+    pub emission: Option<Arc<VoxelGrid>>
+}
+
+impl VoxelVolume {
+    const MAGIC: &'static [u8; 4] = b"VXGR";
+    const VERSION: u32 = 1;
+    const DENSITY: u32 = 1;
+    const TEMPERATURE: u32 = 2;
+    const EMISSION: u32 = 4;
+    // Magic, version, size and channels.
+    const HEADER_SIZE: usize = 24;
+
+    // All the channels are stretched over the bounds.
+    pub fn from_file(path: &Path, bounds: Aabb) -> Result<Self, VolumeLoadError> {
+        std::fs::read(path)
+            .and_then(|bytes| Self::parse(&bytes, bounds))
+            .map_err(|cause| VolumeLoadError { path: path.to_path_buf(), cause })
+    }
+
+    pub fn parse(bytes: &[u8], bounds: Aabb) -> Result<Self, Error> {
+        let invalid = |message: &str| Error::new(ErrorKind::InvalidData, message.to_string());
+        if bytes.len() < 4 || &bytes[0..4] != Self::MAGIC {
+            return Err(invalid("not a voxel volume file"));
+        }
+
+        let mut offset = 4;
+        let mut next = || -> Result<[u8; 4], Error> {
+            let word = bytes.get(offset..offset + 4).ok_or_else(|| invalid("unexpected end of file"))?;
+            offset += 4;
+            Ok([word[0], word[1], word[2], word[3]])
+        };
+
+        if u32::from_le_bytes(next()?) != Self::VERSION {
+            return Err(invalid("unsupported version"));
+        }
+        let width = u32::from_le_bytes(next()?) as usize;
+        let height = u32::from_le_bytes(next()?) as usize;
+        let depth = u32::from_le_bytes(next()?) as usize;
+        let channels = u32::from_le_bytes(next()?);
+        if width == 0 || height == 0 || depth == 0 {
+            return Err(invalid("the grid is empty"));
+        }
+        if channels & Self::DENSITY == 0 {
+            return Err(invalid("the density channel is missing"));
+        }
+        // Checking the size up front so a corrupted header can't overflow the voxel count or allocate more than the file holds.
+        let voxel_count = width.checked_mul(height).and_then(|n| n.checked_mul(depth))
+            .ok_or_else(|| invalid("the grid is too large"))?;
+        let channel_count = (channels & (Self::DENSITY | Self::TEMPERATURE | Self::EMISSION)).count_ones() as usize;
+        let expected_size = voxel_count.checked_mul(4 * channel_count).and_then(|n| n.checked_add(Self::HEADER_SIZE))
+            .ok_or_else(|| invalid("the grid is too large"))?;
+        if bytes.len() < expected_size {
+            return Err(invalid("unexpected end of file"));
+        }
+        if bytes.len() > expected_size {
+            return Err(invalid("unexpected data after the voxels"));
+        }
+
+        let mut read_channel = |flag: u32| -> Result<Option<Arc<VoxelGrid>>, Error> {
+            if channels & flag == 0 {
+                return Ok(None);
+            }
+            let values = (0..voxel_count)
+                .map(|_| next().map(f32::from_le_bytes))
+                .collect::<Result<Vec<f32>, Error>>()?;
+            Ok(Some(Arc::new(VoxelGrid::new(width, height, depth, values, bounds))))
+        };
+        let density = read_channel(Self::DENSITY)?.ok_or_else(|| invalid("the density channel is missing"))?;
+        let temperature = read_channel(Self::TEMPERATURE)?;
+        let emission = read_channel(Self::EMISSION)?;
+        Ok(Self { density, temperature, emission })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3::Pt3;
+
+    fn bounds() -> Aabb {
+        Aabb::new(Pt3::all(0.0), Pt3::all(1.0))
+    }
+
+    // Writes the channels in the same format the loader reads.
+    fn encode(size: [u32; 3], channels: u32, values: &[Vec<f32>]) -> Vec<u8> {
+        let mut bytes = b"VXGR".to_vec();
+        for word in [1, size[0], size[1], size[2], channels] {
+            bytes.extend_from_slice(&u32::to_le_bytes(word));
+        }
+        for value in values.iter().flatten() {
+            bytes.extend_from_slice(&f32::to_le_bytes(*value));
+        }
+        bytes
+    }
+
+    #[test]
+    fn round_trip() {
+        let density: Vec<f32> = (0..24).map(|i| i as f32 * 0.25).collect();
+        let emission: Vec<f32> = (0..24).map(|i| 100.0 - i as f32).collect();
+        let path = std::env::temp_dir().join(format!("voxel_volume_round_trip_{}.vxgr", std::process::id()));
+        std::fs::write(&path, encode([2, 3, 4], 1 | 4, &[density.clone(), emission.clone()])).unwrap();
+        let volume = VoxelVolume::from_file(&path, bounds());
+        std::fs::remove_file(&path).unwrap();
+        let volume = volume.unwrap();
+
+        assert!(volume.temperature.is_none());
+        let emission_grid = volume.emission.unwrap();
+        for z in 0..4 {
+            for y in 0..3 {
+                for x in 0..2 {
+                    let i = (z * 3 + y) * 2 + x;
+                    assert_eq!(volume.density.value(x, y, z), density[i]);
+                    assert_eq!(emission_grid.value(x, y, z), emission[i]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_files() {
+        let values = vec![vec![1.0; 8], vec![1500.0; 8]];
+        let bytes = encode([2, 2, 2], 1 | 2, &values);
+        assert!(VoxelVolume::parse(&bytes, bounds()).is_ok());
+
+        let truncated = VoxelVolume::parse(&bytes[..bytes.len() - 4], bounds()).err().unwrap();
+        assert_eq!(truncated.kind(), ErrorKind::InvalidData);
+        assert!(truncated.to_string().contains("end of file"));
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0..4].copy_from_slice(b"VDB ");
+        assert!(VoxelVolume::parse(&bad_magic, bounds()).err().unwrap().to_string().contains("not a voxel volume"));
+        assert!(VoxelVolume::parse(&bytes[..2], bounds()).is_err());
+
+        let mut bad_version = bytes.clone();
+        bad_version[4..8].copy_from_slice(&u32::to_le_bytes(2));
+        assert!(VoxelVolume::parse(&bad_version, bounds()).is_err());
+        assert!(VoxelVolume::parse(&encode([2, 2, 2], 2, &values[1..]), bounds()).is_err());
+        assert!(VoxelVolume::parse(&encode([0, 2, 2], 1, &[]), bounds()).is_err());
+
+        let mut trailing = bytes.clone();
+        trailing.extend_from_slice(&[0; 4]);
+        let error = VoxelVolume::parse(&trailing, bounds()).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(error.to_string().contains("after the voxels"));
+
+        // Sizes that overflow or that the file is far too small for fail before anything is allocated.
+        let huge = VoxelVolume::parse(&encode([u32::MAX, u32::MAX, u32::MAX], 1, &[]), bounds()).err().unwrap();
+        assert_eq!(huge.kind(), ErrorKind::InvalidData);
+        assert!(huge.to_string().contains("too large"));
+        let large = VoxelVolume::parse(&encode([1 << 16, 1 << 16, 1 << 10], 7, &values), bounds()).err().unwrap();
+        assert_eq!(large.kind(), ErrorKind::InvalidData);
+        assert!(large.to_string().contains("end of file"));
+
+        let error = VoxelVolume::from_file(Path::new("missing.vxgr"), bounds()).err().unwrap();
+        assert_eq!(error.path, Path::new("missing.vxgr"));
+        assert_eq!(error.cause.kind(), ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,180 @@
+use rand::Rng;
+
+// Piecewise constant probability distribution used to choose indices proportionally to their weights.
+pub struct Distribution1D {
+    weights: Vec<f32>,
+    // cdf[i] is the probability of choosing an index lower than i, so it has one more element than the weights.
+    cdf: Vec<f32>,
+    total: f32
+}
+
+impl Distribution1D {
+    pub fn new(weights: Vec<f32>) -> Self {
+        let total: f32 = weights.iter().sum();
+        let mut cdf = Vec::with_capacity(weights.len() + 1);
+        let mut running = 0.0;
+        cdf.push(0.0);
+        for (i, weight) in weights.iter().enumerate() {
+            running += weight;
+            // If every weight is zero all the indices are equally likely.
+            cdf.push(if total > 0.0 { running / total } else { (i + 1) as f32 / weights.len() as f32 });
+        }
+        Self { weights, cdf, total }
+    }
+
+    pub fn total(&self) -> f32 {
+        self.total
+    }
+
+    fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn probability(&self, index: usize) -> f32 {
+        self.cdf[index + 1] - self.cdf[index]
+    }
+
+    // Returns the chosen index and the position inside of it in range <0, 1>.
+    pub fn sample(&self) -> (usize, f32) {
+        let r = rand::thread_rng().gen::<f32>();
+        // Last index whose cdf is not larger than the random value.
+        let index = self.cdf.partition_point(|&value| value <= r).saturating_sub(1).min(self.len() - 1);
+        let probability = self.probability(index);
+        let offset = if probability > 0.0 { (r - self.cdf[index]) / probability } else { 0.5 };
+        (index, offset.clamp(0.0, 1.0))
+    }
+}
+
+// Distribution over a grid. A row is chosen using the sums of the rows and then a column using the weights of that row.
+pub struct Distribution2D {
+    rows: Vec<Distribution1D>,
+    marginal: Distribution1D
+}
+
+impl Distribution2D {
+    pub fn new(weights: &[f32], width: usize, height: usize) -> Self {
+        let rows: Vec<Distribution1D> = weights.chunks(width).take(height).map(|row| Distribution1D::new(row.to_vec())).collect();
+        let marginal = Distribution1D::new(rows.iter().map(Distribution1D::total).collect());
+        Self { rows, marginal }
+    }
+
+    // Returns a point in the unit square and the probability density of choosing it.
+    pub fn sample(&self) -> ((f32, f32), f32) {
+        let (y, y_offset) = self.marginal.sample();
+        let (x, x_offset) = self.rows[y].sample();
+        let point = ((x as f32 + x_offset) / self.rows[y].len() as f32, (y as f32 + y_offset) / self.rows.len() as f32);
+        (point, self.pdf(x, y))
+    }
+
+    // Probability density of the cell at column x and row y. Multiplying by the number of cells
+    // converts the probability of the cell to a density over the unit square.
+    pub fn pdf(&self, x: usize, y: usize) -> f32 {
+        let (width, height) = (self.rows[y].len(), self.rows.len());
+        self.marginal.probability(y) * self.rows[y].probability(x) * (width * height) as f32
+    }
+}
+
+// Chooses indices proportionally to their weights in constant time (Vose's alias method). Each index gets a bucket of the
+// same size that is split between the index itself and one alias, so a sample only needs one random bucket and one coin flip.
+pub struct AliasTable {
+    // Probability of keeping the bucket's own index instead of the alias.
+    thresholds: Vec<f32>,
+    aliases: Vec<usize>,
+    probabilities: Vec<f32>
+}
+
+impl AliasTable {
+    pub fn new(weights: &[f32]) -> Self {
+        let count = weights.len();
+        let total: f32 = weights.iter().sum();
+        let probabilities: Vec<f32> = if total > 0.0 {
+            weights.iter().map(|weight| weight / total).collect()
+        } else {
+            vec![1.0 / count as f32; count]
+        };
+
+        // Buckets with less than the average are filled up with the excess of the ones with more.
+        let mut thresholds: Vec<f32> = probabilities.iter().map(|probability| probability * count as f32).collect();
+        let mut aliases: Vec<usize> = (0..count).collect();
+        let (mut under, mut over): (Vec<usize>, Vec<usize>) = (0..count).partition(|&i| thresholds[i] < 1.0);
+        while let (Some(&small), Some(&large)) = (under.last(), over.last()) {
+            under.pop();
+            aliases[small] = large;
+            thresholds[large] -= 1.0 - thresholds[small];
+            if thresholds[large] < 1.0 {
+                over.pop();
+                under.push(large);
+            }
+        }
+        // Whatever is left is only off because of rounding.
+        for i in under.into_iter().chain(over) {
+            thresholds[i] = 1.0;
+        }
+        Self { thresholds, aliases, probabilities }
+    }
+
+    pub fn probability(&self, index: usize) -> f32 {
+        self.probabilities[index]
+    }
+
+    pub fn sample(&self) -> usize {
+        let bucket = rand::thread_rng().gen_range(0..self.thresholds.len());
+        if rand::thread_rng().gen::<f32>() < self.thresholds[bucket] { bucket } else { self.aliases[bucket] }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: usize = 200_000;
+
+    #[test]
+    fn alias_table_probabilities() {
+        let weights = [1.0, 0.0, 3.0, 6.0, 0.5, 9.5];
+        let table = AliasTable::new(&weights);
+        let mut counts = [0usize; 6];
+        for _ in 0..SAMPLES {
+            counts[table.sample()] += 1;
+        }
+        for (i, weight) in weights.iter().enumerate() {
+            let expected = weight / 20.0;
+            assert!((table.probability(i) - expected).abs() < 1e-6);
+            assert!((counts[i] as f32 / SAMPLES as f32 - expected).abs() < 0.005, "index {} was chosen {} times", i, counts[i]);
+        }
+        assert_eq!(counts[1], 0);
+    }
+
+    #[test]
+    fn alias_table_without_weights_is_uniform() {
+        let table = AliasTable::new(&[0.0; 4]);
+        let mut counts = [0usize; 4];
+        for _ in 0..SAMPLES {
+            counts[table.sample()] += 1;
+        }
+        for (i, count) in counts.iter().enumerate() {
+            assert!((table.probability(i) - 0.25).abs() < 1e-6);
+            assert!((*count as f32 / SAMPLES as f32 - 0.25).abs() < 0.005);
+        }
+    }
+
+    #[test]
+    fn distribution_2d_sample_matches_pdf() {
+        // 4 columns and 3 rows.
+        let weights = [0.0, 1.0, 2.0, 3.0, 4.0, 0.0, 5.0, 1.0, 2.0, 2.0, 2.0, 2.0];
+        let distribution = Distribution2D::new(&weights, 4, 3);
+        let mut counts = [0usize; 12];
+        for _ in 0..SAMPLES {
+            let ((u, v), pdf) = distribution.sample();
+            let (x, y) = (usize::min((u * 4.0) as usize, 3), usize::min((v * 3.0) as usize, 2));
+            assert_eq!(pdf, distribution.pdf(x, y));
+            counts[y * 4 + x] += 1;
+        }
+        for (i, weight) in weights.iter().enumerate() {
+            let probability = weight / 24.0;
+            // The density is spread over a cell that takes up a twelfth of the unit square.
+            assert!((distribution.pdf(i % 4, i / 4) - probability * 12.0).abs() < 1e-5);
+            assert!((counts[i] as f32 / SAMPLES as f32 - probability).abs() < 0.005, "cell {} was chosen {} times", i, counts[i]);
+        }
+    }
+}
@@ -0,0 +1,14 @@
+mod environment;
+pub use environment::*;
+
+mod constant_environment;
+pub use constant_environment::*;
+
+mod gradient_environment;
+pub use gradient_environment::*;
+
+mod environment_map;
+pub use environment_map::*;
+
+mod physical_sky;
+pub use physical_sky::*;
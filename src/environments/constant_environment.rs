@@ -0,0 +1,20 @@
+use crate::vec3::{Vec3, Color};
+
+use super::Environment;
+
+// The same color in every direction.
+pub struct ConstantEnvironment {
+    pub color: Color
+}
+
+impl ConstantEnvironment {
+    pub fn new(color: Color) -> Self {
+        Self { color }
+    }
+}
+
+impl Environment for ConstantEnvironment {
+    fn color(&self, _direction: Vec3) -> Color {
+        self.color
+    }
+}
@@ -0,0 +1,19 @@
+use crate::vec3::{Vec3, Color};
+
+// Light coming from infinitely far away in every direction. Seen by rays that don't hit anything.
+pub trait Environment where Self: Send + Sync {
+    // The direction doesn't have to be normalized.
+    fn color(&self, direction: Vec3) -> Color;
+
+    // Chooses a direction towards the environment with probability proportional to how much light comes from it.
+    // Returns the normalized direction and its probability density with respect to solid angle.
+    // Environments that can't be importance sampled return None.
+    fn sample(&self) -> Option<(Vec3, f32)> {
+        None
+    }
+
+    // Probability density of sample returning the direction.
+    fn pdf(&self, _direction: Vec3) -> f32 {
+        0.0
+    }
+}
@@ -0,0 +1,152 @@
+use std::f32::consts::{PI, TAU};
+use std::path::Path;
+
+use crate::{vec3::{Vec3, Color}, distribution::Distribution2D, textures::TextureLoadError};
+
+use super::Environment;
+
+// Environment stored in an image using the equirectangular projection. The horizontal axis of the image is the angle
+// around the y axis and the vertical axis is the angle from the top. Usually loaded from a .hdr or .exr file.
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    // Going from the top left.
+    pixels: Vec<Color>,
+    // Rotation around the y axis in radians.
+    pub rotation: f32,
+    pub intensity: f32,
+    // Bright parts of the image like the sun get sampled more often.
+    distribution: Distribution2D
+}
+
+impl EnvironmentMap {
+    pub fn from_file(path: &Path, rotation: f32, intensity: f32) -> Result<Self, TextureLoadError> {
+        let image = image::open(path).map_err(|cause| TextureLoadError { path: path.to_path_buf(), cause })?;
+        // Environment maps store linear radiance so the values are not converted like in ImageTexture.
+        let image = image.to_rgb32f();
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let pixels: Vec<Color> = image.pixels().map(|pixel| Color::new(pixel.0[0], pixel.0[1], pixel.0[2])).collect();
+        Ok(Self::new(width, height, pixels, rotation, intensity))
+    }
+
+    // The pixels go row by row from the top left.
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>, rotation: f32, intensity: f32) -> Self {
+        assert_eq!(pixels.len(), width * height, "environment map needs width * height pixels");
+        // Rows near the poles are squeezed into a smaller solid angle so they have to be sampled less.
+        let mut weights = Vec::with_capacity(pixels.len());
+        for y in 0..height {
+            let sin_theta = f32::sin(PI * (y as f32 + 0.5) / height as f32);
+            for x in 0..width {
+                weights.push(Self::luminance(pixels[y * width + x]) * sin_theta);
+            }
+        }
+        let distribution = Distribution2D::new(&weights, width, height);
+        Self { width, height, pixels, rotation, intensity, distribution }
+    }
+
+    fn luminance(color: Color) -> f32 {
+        0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z
+    }
+
+    fn rotated(&self, direction: Vec3, angle: f32) -> Vec3 {
+        let (sin, cos) = angle.sin_cos();
+        Vec3::new(cos * direction.x + sin * direction.z, direction.y, -sin * direction.x + cos * direction.z)
+    }
+
+    // Position in the image in range <0, 1> going from the top left. Uses the same mapping as the texture coordinates of spheres.
+    fn image_coord(&self, direction: Vec3) -> (f32, f32) {
+        let d = self.rotated(direction.normalized(), -self.rotation);
+        let theta = f32::acos(d.y.clamp(-1.0, 1.0));
+        let phi = f32::atan2(-d.z, d.x) + PI;
+        (phi / TAU, theta / PI)
+    }
+
+    fn pixel_index(&self, (u, v): (f32, f32)) -> (usize, usize) {
+        let x = usize::min((u * self.width as f32) as usize, self.width - 1);
+        let y = usize::min((v * self.height as f32) as usize, self.height - 1);
+        (x, y)
+    }
+}
+
+impl Environment for EnvironmentMap {
+    fn color(&self, direction: Vec3) -> Color {
+        let (x, y) = self.pixel_index(self.image_coord(direction));
+        self.intensity * self.pixels[y * self.width + x]
+    }
+
+    fn sample(&self) -> Option<(Vec3, f32)> {
+        let ((u, v), pdf) = self.distribution.sample();
+        let (theta, phi) = (v * PI, u * TAU - PI);
+        let sin_theta = theta.sin();
+        if pdf == 0.0 || sin_theta == 0.0 {
+            return None;
+        }
+        // Inverse of image_coord.
+        let direction = Vec3::new(phi.cos() * sin_theta, theta.cos(), -phi.sin() * sin_theta);
+        // Converting the density over the image to a density over solid angle.
+        Some((self.rotated(direction, self.rotation), pdf / (2.0 * PI * PI * sin_theta)))
+    }
+
+    fn pdf(&self, direction: Vec3) -> f32 {
+        let (u, v) = self.image_coord(direction);
+        let sin_theta = f32::sin(v * PI);
+        if sin_theta == 0.0 {
+            return 0.0;
+        }
+        let (x, y) = self.pixel_index((u, v));
+        self.distribution.pdf(x, y) / (2.0 * PI * PI * sin_theta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: usize = 32;
+    const HEIGHT: usize = 16;
+    const SAMPLES: usize = 100_000;
+
+    // Dim everywhere except for a bright patch like the sun in a sky.
+    fn environment() -> EnvironmentMap {
+        let pixels = (0..WIDTH * HEIGHT).map(|i| {
+            let (x, y) = (i % WIDTH, i / WIDTH);
+            if (10..13).contains(&x) && (4..6).contains(&y) { Color::new(50.0, 40.0, 30.0) } else { Color::all(0.2) }
+        }).collect();
+        EnvironmentMap::new(WIDTH, HEIGHT, pixels, 0.7, 1.5)
+    }
+
+    #[test]
+    fn sample_matches_pdf() {
+        let environment = environment();
+        let mut mismatched = 0;
+        for _ in 0..SAMPLES {
+            let (direction, pdf) = environment.sample().unwrap();
+            assert!((direction.length() - 1.0).abs() < 1e-4);
+            if (pdf - environment.pdf(direction)).abs() > 1e-3 * pdf {
+                mismatched += 1;
+            }
+        }
+        // Directions right on the edge of a pixel can be rounded into the one next to it.
+        assert!(mismatched < SAMPLES / 1000, "{} samples had a different pdf", mismatched);
+
+        // The density over the whole sphere adds up to 1.
+        let total: f32 = (0..SAMPLES).map(|_| environment.pdf(Vec3::random_unit())).sum();
+        assert!((4.0 * PI * total / SAMPLES as f32 - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn samples_add_up_to_the_light_of_the_map() {
+        let environment = environment();
+        // Each pixel covers the angles between its edges.
+        let mut expected = 0.0;
+        for y in 0..HEIGHT {
+            let solid_angle = TAU / WIDTH as f32 * (f32::cos(PI * y as f32 / HEIGHT as f32) - f32::cos(PI * (y + 1) as f32 / HEIGHT as f32));
+            for x in 0..WIDTH {
+                expected += environment.intensity * environment.pixels[y * WIDTH + x].x * solid_angle;
+            }
+        }
+        let total: f32 = (0..SAMPLES).filter_map(|_| environment.sample()).map(|(direction, pdf)| environment.color(direction).x / pdf).sum();
+        let estimate = total / SAMPLES as f32;
+        assert!((estimate - expected).abs() < 0.01 * expected, "estimated {} instead of {}", estimate, expected);
+    }
+}
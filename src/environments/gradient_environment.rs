@@ -0,0 +1,28 @@
+use crate::vec3::{Vec3, Color};
+
+use super::Environment;
+
+// Blends between two colors based on how much the direction points up. The classic sky from the book
+// is a gradient from white at the bottom to light blue at the top.
+pub struct GradientEnvironment {
+    pub bottom: Color,
+    pub top: Color
+}
+
+impl GradientEnvironment {
+    pub fn new(bottom: Color, top: Color) -> Self {
+        Self { bottom, top }
+    }
+
+    pub fn sky() -> Self {
+        Self::new(Color::all(1.0), Color::new(0.5, 0.7, 1.0))
+    }
+}
+
+impl Environment for GradientEnvironment {
+    fn color(&self, direction: Vec3) -> Color {
+        // Maps y from <-1, 1> to <0, 1>.
+        let t = 0.5 * (direction.normalized().y + 1.0);
+        (1.0 - t) * self.bottom + t * self.top
+    }
+}
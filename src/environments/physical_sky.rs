@@ -0,0 +1,232 @@
+use std::f32::consts::{PI, TAU};
+
+use rand::Rng;
+
+use crate::{vec3::{Vec3, Color}, onb::Onb};
+
+use super::Environment;
+
+// Coefficients of the Perez sky distribution for one of the channels of the Yxy color space.
+#[derive(Debug, Clone, Copy)]
+struct Perez {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    e: f32
+}
+
+impl Perez {
+    // Relative brightness of the sky at angle theta from the zenith and angle gamma from the sun.
+    fn value(&self, cos_theta: f32, gamma: f32) -> f32 {
+        let cos_gamma = gamma.cos();
+        (1.0 + self.a * f32::exp(self.b / cos_theta.max(0.01))) * (1.0 + self.c * f32::exp(self.d * gamma) + self.e * cos_gamma * cos_gamma)
+    }
+}
+
+// Analytic daylight sky by Preetham, Shirley and Smits ("A Practical Analytic Model for Daylight").
+// Includes a sun disk with its color changed by the atmosphere. The sun can be sampled directly.
+pub struct PhysicalSky {
+    sun_direction: Vec3,
+    sun_angular_radius: f32,
+    perez: [Perez; 3],
+    // Yxy color at the zenith divided by the perez function at the zenith so other directions only need a multiplication.
+    zenith: [f32; 3],
+    sun_radiance: Color,
+    ground_radiance: Color,
+    // Scales the sky from kcd/m^2 to the units used by the scene.
+    pub intensity: f32
+}
+
+impl PhysicalSky {
+    // Elevation is the angle of the sun above the horizon and azimuth is the angle around the y axis
+    // starting from the z axis towards the x axis. Both in radians.
+    // Turbidity is the amount of haze in the air. 2 is a very clear sky and 10 a hazy one.
+    pub fn new(sun_elevation: f32, sun_azimuth: f32, turbidity: f32, ground_albedo: Color) -> Self {
+        // The real sun covers about half of a degree. Bigger suns create softer shadows.
+        const SUN_ANGULAR_RADIUS: f32 = 0.00465;
+        Self::with_sun_size(sun_elevation, sun_azimuth, turbidity, ground_albedo, SUN_ANGULAR_RADIUS)
+    }
+
+    pub fn with_sun_size(sun_elevation: f32, sun_azimuth: f32, turbidity: f32, ground_albedo: Color, sun_angular_radius: f32) -> Self {
+        let t = turbidity;
+        let sun_direction = Vec3::new(
+            sun_elevation.cos() * sun_azimuth.sin(),
+            sun_elevation.sin(),
+            sun_elevation.cos() * sun_azimuth.cos());
+        // Angle of the sun from the zenith. The model isn't defined for a sun below the horizon.
+        let theta_sun = (PI / 2.0 - sun_elevation).clamp(0.0, PI / 2.0 - 0.01);
+
+        let perez = [
+            Perez { a: 0.1787 * t - 1.4630, b: -0.3554 * t + 0.4275, c: -0.0227 * t + 5.3251, d: 0.1206 * t - 2.5771, e: -0.0670 * t + 0.3703 },
+            Perez { a: -0.0193 * t - 0.2592, b: -0.0665 * t + 0.0008, c: -0.0004 * t + 0.2125, d: -0.0641 * t - 0.8989, e: -0.0033 * t + 0.0452 },
+            Perez { a: -0.0167 * t - 0.2608, b: -0.0950 * t + 0.0092, c: -0.0079 * t + 0.2102, d: -0.0441 * t - 1.6537, e: -0.0109 * t + 0.0529 }
+        ];
+
+        // Fitted formulas for the color of the zenith.
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_sun);
+        let zenith_luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        let chromaticity = |m: [[f32; 4]; 3]| {
+            let thetas = [theta_sun.powi(3), theta_sun.powi(2), theta_sun, 1.0];
+            let ts = [t * t, t, 1.0];
+            let mut sum = 0.0;
+            for (i, row) in m.iter().enumerate() {
+                for (j, value) in row.iter().enumerate() {
+                    sum += ts[i] * value * thetas[j];
+                }
+            }
+            sum
+        };
+        let zenith_x = chromaticity([
+            [0.00166, -0.00375, 0.00209, 0.0],
+            [-0.02903, 0.06377, -0.03202, 0.00394],
+            [0.11693, -0.21196, 0.06052, 0.25886]]);
+        let zenith_y = chromaticity([
+            [0.00275, -0.00610, 0.00317, 0.0],
+            [-0.04214, 0.08970, -0.04153, 0.00516],
+            [0.15346, -0.26756, 0.06670, 0.26688]]);
+        let zenith_yxy = [zenith_luminance, zenith_x, zenith_y];
+        let mut zenith = [0.0; 3];
+        for i in 0..3 {
+            zenith[i] = zenith_yxy[i] / perez[i].value(1.0, theta_sun);
+        }
+
+        let mut sky = Self {
+            sun_direction,
+            sun_angular_radius,
+            perez,
+            zenith,
+            sun_radiance: Color::all(0.0),
+            ground_radiance: Color::all(0.0),
+            intensity: 0.05
+        };
+        sky.sun_radiance = Self::sun_radiance(theta_sun, t, sun_angular_radius);
+
+        // The ground is lit by the sun and the sky. The sky is approximated by the color at the zenith.
+        let sun_irradiance = sky.sun_radiance * Self::cone_solid_angle(sun_angular_radius) * sun_elevation.sin().max(0.0);
+        let sky_irradiance = PI * sky.sky_color(Vec3::new(0.0, 1.0, 0.0));
+        sky.ground_radiance = ground_albedo * (sun_irradiance + sky_irradiance) / PI;
+        sky
+    }
+
+    fn cone_solid_angle(angular_radius: f32) -> f32 {
+        TAU * (1.0 - angular_radius.cos())
+    }
+
+    // Light from the sun is scattered by air molecules (Rayleigh) and by haze (aerosols) on the way through the atmosphere.
+    // Shorter wavelengths get scattered more which makes the sun yellow and red near the horizon.
+    fn sun_radiance(theta_sun: f32, turbidity: f32, angular_radius: f32) -> Color {
+        // Illuminance of the sun outside of the atmosphere in klux, the same scale as the sky.
+        const SUN_ILLUMINANCE: f32 = 128.0;
+        // Optical depths of air for the wavelengths of the red, green and blue channels (680nm, 550nm and 440nm).
+        const RAYLEIGH: [f32; 3] = [0.0401, 0.0976, 0.2396];
+        // Wavelengths in micrometers raised to -1.3 (Angstrom's exponent).
+        const AEROSOL: [f32; 3] = [1.651, 2.177, 2.907];
+
+        // Relative length of the path through the atmosphere (Kasten and Young).
+        let degrees = theta_sun.to_degrees();
+        let air_mass = 1.0 / (theta_sun.cos() + 0.15 * f32::powf(93.885 - degrees, -1.253));
+        let beta = 0.04608 * turbidity - 0.04586;
+        let mut transmittance = Color::all(0.0);
+        for i in 0..3 {
+            transmittance[i] = f32::exp(-air_mass * (RAYLEIGH[i] + beta * AEROSOL[i]));
+        }
+        SUN_ILLUMINANCE / Self::cone_solid_angle(angular_radius) * transmittance
+    }
+
+    // Color of the sky without the sun in kcd/m^2.
+    fn sky_color(&self, direction: Vec3) -> Color {
+        let cos_theta = direction.y;
+        let gamma = f32::acos(Vec3::dot(direction, self.sun_direction).clamp(-1.0, 1.0));
+        let [luminance, x, y] = [0, 1, 2].map(|i| self.zenith[i] * self.perez[i].value(cos_theta, gamma));
+        if y <= 0.0 {
+            return Color::all(0.0);
+        }
+
+        // Yxy to XYZ to linear sRGB.
+        let (cx, cy, cz) = (x / y * luminance, luminance, (1.0 - x - y) / y * luminance);
+        Color::new(
+            (3.2406 * cx - 1.5372 * cy - 0.4986 * cz).max(0.0),
+            (-0.9689 * cx + 1.8758 * cy + 0.0415 * cz).max(0.0),
+            (0.0557 * cx - 0.2040 * cy + 1.0570 * cz).max(0.0))
+    }
+
+    fn is_inside_sun(&self, direction: Vec3) -> bool {
+        Vec3::dot(direction, self.sun_direction) >= self.sun_angular_radius.cos()
+    }
+
+    // Probability of sampling the sun instead of the sky.
+    fn sun_probability(&self) -> f32 {
+        if self.sun_direction.y > 0.0 { 0.5 } else { 0.0 }
+    }
+}
+
+impl Environment for PhysicalSky {
+    fn color(&self, direction: Vec3) -> Color {
+        let direction = direction.normalized();
+        if direction.y < 0.0 {
+            return self.intensity * self.ground_radiance;
+        }
+        let sun = if self.is_inside_sun(direction) { self.sun_radiance } else { Color::all(0.0) };
+        self.intensity * (self.sky_color(direction) + sun)
+    }
+
+    // The sun is much brighter than the rest of the sky so half of the samples go to it and the rest is uniform.
+    fn sample(&self) -> Option<(Vec3, f32)> {
+        let direction = if rand::thread_rng().gen::<f32>() < self.sun_probability() {
+            // Uniform direction inside of the cone of the sun.
+            let (r1, r2): (f32, f32) = (rand::thread_rng().gen(), rand::thread_rng().gen());
+            let cos = 1.0 - r1 * (1.0 - self.sun_angular_radius.cos());
+            let sin = f32::sqrt(1.0 - cos * cos);
+            let phi = TAU * r2;
+            Onb::from_w(self.sun_direction).local(Vec3::new(sin * phi.cos(), sin * phi.sin(), cos))
+        } else {
+            Vec3::random_unit()
+        };
+        Some((direction, self.pdf(direction)))
+    }
+
+    fn pdf(&self, direction: Vec3) -> f32 {
+        let sun_probability = self.sun_probability();
+        let sun = if self.is_inside_sun(direction.normalized()) { 1.0 / Self::cone_solid_angle(self.sun_angular_radius) } else { 0.0 };
+        sun_probability * sun + (1.0 - sun_probability) / (4.0 * PI)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: usize = 200_000;
+
+    #[test]
+    fn half_of_the_samples_go_to_the_sun() {
+        let sky = PhysicalSky::new(0.5, 1.0, 3.0, Color::all(0.3));
+        let mut inside_sun = 0;
+        for _ in 0..SAMPLES {
+            let (direction, pdf) = sky.sample().unwrap();
+            assert!((pdf - sky.pdf(direction)).abs() <= 1e-4 * pdf);
+            if sky.is_inside_sun(direction) {
+                inside_sun += 1;
+            }
+        }
+        assert!((inside_sun as f32 / SAMPLES as f32 - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn pdf_adds_up_to_one() {
+        // A big sun so enough uniform directions land inside of it.
+        let sky = PhysicalSky::with_sun_size(0.8, 0.0, 3.0, Color::all(0.3), 0.4);
+        let total: f32 = (0..SAMPLES).map(|_| sky.pdf(Vec3::random_unit())).sum();
+        assert!((4.0 * PI * total / SAMPLES as f32 - 1.0).abs() < 0.03);
+    }
+
+    #[test]
+    fn sun_below_the_horizon_is_not_sampled() {
+        let sky = PhysicalSky::new(-0.2, 0.0, 3.0, Color::all(0.3));
+        for _ in 0..1000 {
+            let (_, pdf) = sky.sample().unwrap();
+            assert!((pdf - 1.0 / (4.0 * PI)).abs() < 1e-6);
+        }
+    }
+}
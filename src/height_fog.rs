@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::{materials::{Material, HenyeyGreenstein}, textures::SolidColor, hittable_objects::HitRecord, vec3::Color, vec2::Vec2, ray::Ray};
+
+// Haze filling the whole scene without a boundary object. The density decreases exponentially with the height,
+// density * e^(-falloff * (y - base_height)), like the air of a real atmosphere, so valleys are foggy and the sky stays clear.
+// The exponential can be integrated along a ray so distances are sampled exactly without any tracking.
+pub struct HeightFog {
+    // Density at the base height.
+    pub density: f32,
+    pub base_height: f32,
+    // How fast the fog thins out going up. 0 makes it the same everywhere, which also hides the environment completely.
+    pub falloff: f32,
+    pub phase_function: Arc<dyn Material>
+}
+
+impl HeightFog {
+    pub fn new(density: f32, albedo: Color, base_height: f32, falloff: f32) -> Self {
+        let phase_function = Arc::new(HenyeyGreenstein::new(Arc::new(SolidColor::new(albedo)), 0.0));
+        Self { density, base_height, falloff, phase_function }
+    }
+
+    // Fog density at the origin of the ray multiplied by the length of the direction and how fast the density
+    // changes with t, so the density along the ray is scale * e^(-rate * t).
+    fn along_ray(&self, ray: &Ray) -> (f32, f32) {
+        let scale = ray.direction.length() * self.density * f32::exp(-self.falloff * (ray.origin.y - self.base_height));
+        (scale, self.falloff * ray.direction.y)
+    }
+
+    // Integral of the density between t_min and t_max.
+    fn optical_depth(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        if self.density <= 0.0 || t_max <= t_min {
+            return 0.0;
+        }
+        let (scale, rate) = self.along_ray(ray);
+        if rate.abs() < 1e-6 {
+            return scale * (t_max - t_min);
+        }
+        scale * (f32::exp(-rate * t_min) - f32::exp(-rate * t_max)) / rate
+    }
+
+    // Scatters the ray before t_max with the probability of hitting a particle on the way, the same as a medium.
+    pub fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        if self.density <= 0.0 {
+            return None;
+        }
+        // Finding the t where the optical depth reaches a randomly chosen value by inverting the integral.
+        let depth = -f32::ln(1.0 - rand::thread_rng().gen::<f32>());
+        let (scale, rate) = self.along_ray(ray);
+        let t = if rate.abs() < 1e-6 {
+            t_min + depth / scale
+        } else {
+            // Going up the fog gets thinner so the total depth is limited and the ray might never scatter.
+            let remaining = f32::exp(-rate * t_min) - depth * rate / scale;
+            if remaining <= 0.0 {
+                return None;
+            }
+            -f32::ln(remaining) / rate
+        };
+        if t >= t_max {
+            return None;
+        }
+        // The normal doesn't mean anything inside of a medium. Pointing it against the ray keeps it on the front face.
+        let ray_length = ray.direction.length();
+        Some(HitRecord::new(ray.at(t), ray, -ray.direction / ray_length, t, Vec2::all(0.0), self.phase_function.clone()))
+    }
+
+    // Beer's law with the integrated density.
+    pub fn transmittance(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        f32::exp(-self.optical_depth(ray, t_min, t_max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec3::Vec3;
+
+    // Fraction of rays that get through without scattering.
+    fn miss_fraction(fog: &HeightFog, ray: &Ray, t_max: f32) -> f32 {
+        const SAMPLES: usize = 100_000;
+        let misses = (0..SAMPLES).filter(|_| match fog.hit(ray, 0.0, t_max) {
+            Some(hit) => {
+                assert!(hit.t >= 0.0 && hit.t < t_max);
+                false
+            },
+            None => true
+        }).count();
+        misses as f32 / SAMPLES as f32
+    }
+
+    #[test]
+    fn miss_fraction_matches_transmittance() {
+        let fog = HeightFog::new(0.3, Color::all(0.8), 1.0, 0.5);
+        // The density at the height of the rays' origin.
+        let density = 0.3 * f32::exp(-0.5);
+        let origin = Vec3::new(0.0, 2.0, 0.0);
+        let rays = [
+            // Up forever, the fog thins out so some light still gets through.
+            (Ray::new(origin, Vec3::new(0.0, 1.0, 0.0)), f32::INFINITY, density / 0.5),
+            // Down to the ground at y = 0.
+            (Ray::new(origin, Vec3::new(0.0, -2.0, 0.0)), 1.0, density * f32::exp_m1(1.0) / 0.5),
+            // Sideways the density stays the same.
+            (Ray::new(origin, Vec3::new(1.0, 0.0, 0.0)), 5.0, density * 5.0)
+        ];
+        for (ray, t_max, depth) in rays {
+            let expected = f32::exp(-depth);
+            assert!((fog.transmittance(&ray, 0.0, t_max) - expected).abs() < 1e-4);
+            let misses = miss_fraction(&fog, &ray, t_max);
+            assert!((misses - expected).abs() < 0.01, "{} of the rays missed instead of {}", misses, expected);
+        }
+    }
+}
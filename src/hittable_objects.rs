@@ -23,4 +23,13 @@ mod rotate_y;
 pub use rotate_y::*;
 
 mod constant_medium;
-pub use constant_medium::*;
\ No newline at end of file
+pub use constant_medium::*;
+
+mod heterogeneous_medium;
+pub use heterogeneous_medium::*;
+
+mod alpha_mask;
+pub use alpha_mask::*;
+
+mod flip_face;
+pub use flip_face::*;
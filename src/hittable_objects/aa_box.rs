@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use crate::{vec3::Pt3, materials::Material, ray::Ray, aabb::Aabb};
 
-use super::{HittableList, XyRect, XzRect, YzRect, Hittable, HitRecord};
+use super::{HittableList, XyRect, XzRect, YzRect, FlipFace, Hittable, HitRecord};
 
 pub struct AaBox {
     aabb: Aabb,
@@ -11,13 +11,15 @@ pub struct AaBox {
 
 impl AaBox {
     pub fn new(min: Pt3, max: Pt3, material: Arc<dyn Material>) -> Self {
+        // The normals of the rectangles point towards the positive side of the axis,
+        // so the sides at the min corner are flipped to make every side face outwards.
         let mut sides = HittableList::new();
         sides.add(Arc::new(XyRect::new(min.x, max.x, min.y, max.y, max.z, material.clone())));
-        sides.add(Arc::new(XyRect::new(min.x, max.x, min.y, max.y, min.z, material.clone())));
+        sides.add(Arc::new(FlipFace::new(Arc::new(XyRect::new(min.x, max.x, min.y, max.y, min.z, material.clone())))));
         sides.add(Arc::new(XzRect::new(min.x, max.x, min.z, max.z, max.y, material.clone())));
-        sides.add(Arc::new(XzRect::new(min.x, max.x, min.z, max.z, min.y, material.clone())));
+        sides.add(Arc::new(FlipFace::new(Arc::new(XzRect::new(min.x, max.x, min.z, max.z, min.y, material.clone())))));
         sides.add(Arc::new(YzRect::new(min.y, max.y, min.z, max.z, max.x, material.clone())));
-        sides.add(Arc::new(YzRect::new(min.y, max.y, min.z, max.z, min.x, material)));
+        sides.add(Arc::new(FlipFace::new(Arc::new(YzRect::new(min.y, max.y, min.z, max.z, min.x, material)))));
         AaBox{ aabb: Aabb::new(min, max), sides }
     }
 }
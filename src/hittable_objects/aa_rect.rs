@@ -1,9 +1,28 @@
 use std::sync::Arc;
 
-use crate::{aabb::Aabb, vec3::Vec3, ray::Ray, materials::Material, vec2::Vec2};
+use rand::Rng;
+
+use crate::{aabb::Aabb, vec3::{Vec3, Pt3}, ray::Ray, materials::Material, vec2::Vec2};
 
 use super::{Hittable, HitRecord};
 
+// Points are chosen uniformly on the rects. Seen from the origin a small patch of the rect covers less solid angle
+// the further away and the more tilted it is, so the density per solid angle is the squared distance over the
+// area times the cosine. `normal_part` is the component of the direction along the normal of the rect.
+fn rect_pdf(hit: Option<HitRecord>, direction: Vec3, normal_part: f32, area: f32) -> f32 {
+    let Some(hit) = hit else { return 0.0 };
+    let distance_squared = hit.t * hit.t * direction.length_squared();
+    let cos = normal_part.abs() / direction.length();
+    if cos <= 0.0 || area <= 0.0 {
+        return 0.0;
+    }
+    distance_squared / (cos * area)
+}
+
+fn random_between(min: f32, max: f32) -> f32 {
+    min + rand::thread_rng().gen::<f32>() * (max - min)
+}
+
 // Could use macros to generate all the versions or use a single function and pass the arguments.
 // Not using Vec2 for min and max because it might be confusing for other planes.
 pub struct XyRect {
@@ -41,7 +60,8 @@ impl Hittable for XyRect {
             Vec3::new(0.0, 0.0, 1.0), 
             t, 
             Vec2::new((x - self.x_min) / (self.x_max - self.x_min), (y - self.y_min) / (self.y_max - self.y_min)),
-            self.material.clone()))
+            self.material.clone())
+            .with_tangents(Vec3::new(self.x_max - self.x_min, 0.0, 0.0), Vec3::new(0.0, self.y_max - self.y_min, 0.0)))
     }
 
     fn bounding_box(&self) -> Option<Aabb> {
@@ -49,6 +69,15 @@ impl Hittable for XyRect {
             Vec3::new(self.x_min, self.y_min, self.z - 0.0001), 
             Vec3::new(self.x_max, self.y_max, self.z + 0.0001)))
     }
+
+    fn pdf_value(&self, origin: Pt3, direction: Vec3) -> f32 {
+        let hit = self.hit(&Ray::new(origin, direction), 0.001, f32::INFINITY);
+        rect_pdf(hit, direction, direction.z, (self.x_max - self.x_min) * (self.y_max - self.y_min))
+    }
+
+    fn random(&self, origin: Pt3) -> Vec3 {
+        Pt3::new(random_between(self.x_min, self.x_max), random_between(self.y_min, self.y_max), self.z) - origin
+    }
 }
 
 pub struct XzRect {
@@ -86,7 +115,8 @@ impl Hittable for XzRect {
             Vec3::new(0.0, 1.0, 0.0), 
             t, 
             Vec2::new((x - self.x_min) / (self.x_max - self.x_min), (z - self.z_min) / (self.z_max - self.z_min)),
-            self.material.clone()))
+            self.material.clone())
+            .with_tangents(Vec3::new(self.x_max - self.x_min, 0.0, 0.0), Vec3::new(0.0, 0.0, self.z_max - self.z_min)))
     }
 
     fn bounding_box(&self) -> Option<Aabb> {
@@ -94,6 +124,15 @@ impl Hittable for XzRect {
             Vec3::new(self.x_min, self.y - 0.0001, self.z_min), 
             Vec3::new(self.x_max, self.y + 0.0001, self.z_max)))
     }
+
+    fn pdf_value(&self, origin: Pt3, direction: Vec3) -> f32 {
+        let hit = self.hit(&Ray::new(origin, direction), 0.001, f32::INFINITY);
+        rect_pdf(hit, direction, direction.y, (self.x_max - self.x_min) * (self.z_max - self.z_min))
+    }
+
+    fn random(&self, origin: Pt3) -> Vec3 {
+        Pt3::new(random_between(self.x_min, self.x_max), self.y, random_between(self.z_min, self.z_max)) - origin
+    }
 }
 
 pub struct YzRect {
@@ -131,7 +170,8 @@ impl Hittable for YzRect {
             Vec3::new(1.0, 0.0,  0.0), 
             t, 
             Vec2::new((y - self.y_min) / (self.y_max - self.y_min), (z - self.z_min) / (self.z_max - self.z_min)),
-            self.material.clone()))
+            self.material.clone())
+            .with_tangents(Vec3::new(0.0, self.y_max - self.y_min, 0.0), Vec3::new(0.0, 0.0, self.z_max - self.z_min)))
     }
 
     fn bounding_box(&self) -> Option<Aabb> {
@@ -139,4 +179,13 @@ impl Hittable for YzRect {
             Vec3::new(self.x - 0.0001, self.y_min,  self.z_min), 
             Vec3::new(self.x + 0.0001, self.y_max,  self.z_max)))
     }
+
+    fn pdf_value(&self, origin: Pt3, direction: Vec3) -> f32 {
+        let hit = self.hit(&Ray::new(origin, direction), 0.001, f32::INFINITY);
+        rect_pdf(hit, direction, direction.x, (self.y_max - self.y_min) * (self.z_max - self.z_min))
+    }
+
+    fn random(&self, origin: Pt3) -> Vec3 {
+        Pt3::new(self.x, random_between(self.y_min, self.y_max), random_between(self.z_min, self.z_max)) - origin
+    }
 }
\ No newline at end of file
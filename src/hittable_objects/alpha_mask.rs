@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::{aabb::Aabb, ray::Ray, textures::Texture, vec3::{Vec3, Pt3}};
+
+use super::{Hittable, HitRecord};
+
+// Makes parts of a surface transparent. Used for things like leaves, fences and decals which are modelled as simple quads.
+pub struct AlphaMask {
+    pub hittable: Arc<dyn Hittable>,
+    // 0 is fully transparent and 1 is fully opaque.
+    pub opacity: Arc<dyn Texture>
+}
+
+impl AlphaMask {
+    pub fn new(hittable: Arc<dyn Hittable>, opacity: Arc<dyn Texture>) -> Self {
+        Self { hittable, opacity }
+    }
+}
+
+impl Hittable for AlphaMask {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let mut t_min = t_min;
+        loop {
+            let hit = self.hittable.hit(ray, t_min, t_max)?;
+            // Treating opacity as the probability of the ray stopping makes partially transparent surfaces
+            // let through the right amount of light on average. This also works for shadow rays because they use the same function.
+            let opacity = self.opacity.value_at(&hit);
+            if opacity > rand::thread_rng().gen() {
+                return Some(hit);
+            }
+            // The intersection continues behind the masked out point.
+            t_min = hit.t + 0.0001;
+        }
+    }
+
+    // Shadow rays let through the transparent part of every surface they cross, which is what hit does on average
+    // but without the noise.
+    fn transmittance(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        let mut t_min = t_min;
+        let mut transmittance = 1.0;
+        while let Some(hit) = self.hittable.hit(ray, t_min, t_max) {
+            transmittance *= 1.0 - self.opacity.value_at(&hit).clamp(0.0, 1.0);
+            if transmittance == 0.0 {
+                break;
+            }
+            t_min = hit.t + 0.0001;
+        }
+        transmittance
+    }
+
+    // Masked lights are still sampled over their whole shape. The transparent parts are skipped by the visibility test.
+    fn pdf_value(&self, origin: Pt3, direction: Vec3) -> f32 {
+        self.hittable.pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: Pt3) -> Vec3 {
+        self.hittable.random(origin)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.hittable.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, RgbaImage, Rgba};
+    use crate::{hittable_objects::{HittableList, XyRect}, materials::{Material, Lambertian}, textures::{ImageTexture, AlphaChannel, ColorSpace}, vec3::Color};
+
+    #[test]
+    fn transparent_texels_let_rays_through() {
+        let material: Arc<dyn Material> = Arc::new(Lambertian::from_color(Color::all(0.5)));
+        // The left half is fully transparent and the right half opaque.
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_fn(2, 1, |x, _| Rgba([255, 255, 255, if x == 0 { 0 } else { 255 }])));
+        let opacity = Arc::new(AlphaChannel::new(Arc::new(ImageTexture::from_image(image, ColorSpace::Srgb))));
+        let mask = AlphaMask::new(Arc::new(XyRect::new(0.0, 2.0, 0.0, 1.0, 1.0, material.clone())), opacity);
+        let mut world = HittableList::new();
+        world.add(Arc::new(mask));
+        world.add(Arc::new(XyRect::new(0.0, 2.0, 0.0, 1.0, 3.0, material)));
+
+        // Through the center of the transparent texel the ray reaches the rect behind.
+        let transparent = Ray::new(Vec3::new(0.5, 0.5, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        for _ in 0..100 {
+            assert!((world.hit(&transparent, 0.001, f32::INFINITY).unwrap().t - 3.0).abs() < 1e-4);
+        }
+        assert_eq!(world.objects[0].transmittance(&transparent, 0.001, f32::INFINITY), 1.0);
+        assert_eq!(world.objects[0].transmittance(&transparent, 0.001, 2.0), 1.0);
+
+        let opaque = Ray::new(Vec3::new(1.5, 0.5, 0.0), Vec3::new(0.0, 0.0, 1.0));
+        for _ in 0..100 {
+            assert!((world.hit(&opaque, 0.001, f32::INFINITY).unwrap().t - 1.0).abs() < 1e-4);
+        }
+        assert_eq!(world.objects[0].transmittance(&opaque, 0.001, 2.0), 0.0);
+    }
+}
@@ -78,6 +78,17 @@ impl Hittable for BhvNode {
         if let Some(_) = hit_right { hit_right } else { hit_left }
     }
 
+    fn transmittance(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        if !self.aabb.hit(ray, t_min, t_max) {
+            return 1.0;
+        }
+        let left = self.left.transmittance(ray, t_min, t_max);
+        if left == 0.0 {
+            return 0.0;
+        }
+        left * self.right.transmittance(ray, t_min, t_max)
+    }
+
     fn bounding_box(&self) -> Option<Aabb> {
         Some(self.aabb)
     }
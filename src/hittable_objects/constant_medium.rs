@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use rand::Rng;
 
-use crate::{materials::Material, ray::Ray, aabb::Aabb, vec3::Vec3, vec2::Vec2};
+use crate::{materials::Material, ray::Ray, aabb::Aabb, vec2::Vec2};
 
 use super::{Hittable, HitRecord};
 
@@ -20,47 +20,160 @@ impl ConstantMedium {
 
 impl Hittable for ConstantMedium {
     fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
-        // Print occasional samples when debugging. To enable, set enableDebug true.
-        // const bool enableDebug = false;
-        // const bool debugging = enableDebug && random_double() < 0.00001;
+        let ray_length = ray.direction.length();
+        // The chance of hitting a particle doesn't depend on the distance already travelled, so a single distance
+        // can be sampled for the whole ray and used up by the parts of the ray that are inside.
+        let mut hit_distance = self.density_inverse_negated * f32::ln(rand::thread_rng().gen_range(0.0..1.0));
+
+        for (t1, t2) in self.boundary.inside_intervals(ray, t_min, t_max) {
+            let distance_inside_boundary = (t2 - t1) * ray_length;
+            if hit_distance <= distance_inside_boundary {
+                let t = t1 + hit_distance / ray_length;
+                // The normal doesn't mean anything inside of a medium. Pointing it against the ray keeps it on the front face.
+                return Some(HitRecord::new(ray.at(t), ray, -ray.direction / ray_length, t, Vec2::all(0.0), self.phase_function.clone()));
+            }
+            hit_distance -= distance_inside_boundary;
+        }
+        None
+    }
 
+    // Beer's law. The light decreases exponentially with the distance travelled inside.
+    fn transmittance(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        let ray_length = ray.direction.length();
+        let distance: f32 = self.boundary.inside_intervals(ray, t_min, t_max).iter()
+            .map(|(t1, t2)| (t2 - t1) * ray_length)
+            .sum();
+        f32::exp(distance / self.density_inverse_negated)
+    }
 
-        let mut hit1 = self.boundary.hit(ray, -f32::INFINITY, f32::INFINITY)?;
-        let mut hit2 = self.boundary.hit(ray, hit1.t + 0.0001, f32::INFINITY)?;
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+}
 
-        // if (debugging) std::cerr << "\nt_min=" << rec1.t << ", t_max=" << rec2.t << '\n';
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{PI, SQRT_2};
+    use crate::hittable_objects::{HittableList, Sphere, AaBox, RotateY, Translate};
+    use crate::materials::Isotropic;
+    use crate::textures::SolidColor;
+    use crate::vec3::Vec3;
+
+    fn medium(boundary: Arc<dyn Hittable>, density: f32) -> ConstantMedium {
+        let phase_function: Arc<dyn Material> = Arc::new(Isotropic::new(Arc::new(SolidColor::new(Vec3::all(1.0)))));
+        ConstantMedium::new(boundary, phase_function, density)
+    }
 
-        if hit1.t < t_min {
-            hit1.t = t_min;
-        }
+    fn sphere(center: Vec3, radius: f32) -> Arc<dyn Hittable> {
+        let material: Arc<dyn Material> = Arc::new(Isotropic::new(Arc::new(SolidColor::new(Vec3::all(1.0)))));
+        Arc::new(Sphere::new(center, radius, material))
+    }
 
-        if hit2.t > t_max {
-            hit2.t = t_max;
-        }
+    // Fraction of rays that get through without hitting a particle.
+    fn miss_fraction(medium: &ConstantMedium, ray: &Ray, t_max: f32) -> f32 {
+        const SAMPLES: usize = 100_000;
+        let misses = (0..SAMPLES).filter(|_| medium.hit(ray, 0.001, t_max).is_none()).count();
+        misses as f32 / SAMPLES as f32
+    }
 
-        if hit1.t >= hit2.t {
-            return None;
+    #[test]
+    fn ray_starting_inside() {
+        let medium = medium(sphere(Vec3::all(0.0), 2.0), 0.5);
+        // Starts at the center so only the radius is inside.
+        let ray = Ray::new(Vec3::all(0.0), Vec3::new(0.0, 0.0, 1.0));
+        let expected = f32::exp(-0.5 * 2.0);
+
+        assert!((medium.transmittance(&ray, 0.001, f32::INFINITY) - expected).abs() < 1e-3);
+        assert!((miss_fraction(&medium, &ray, f32::INFINITY) - expected).abs() < 0.01);
+        for _ in 0..1000 {
+            if let Some(hit) = medium.hit(&ray, 0.001, f32::INFINITY) {
+                assert!(hit.t > 0.0 && hit.t <= 2.0);
+                assert!(hit.is_front_face);
+            }
         }
+    }
 
-        let ray_length = ray.direction.length();
-        let distance_inside_boundary = (hit2.t - hit1.t) * ray_length;
-        let hit_distance = self.density_inverse_negated * f32::ln(rand::thread_rng().gen_range(0.0..1.0));
-
-        if hit_distance > distance_inside_boundary {
-            return None;
+    #[test]
+    fn two_disjoint_spheres() {
+        let mut boundary = HittableList::new();
+        boundary.add(sphere(Vec3::new(0.0, 0.0, 3.0), 1.0));
+        boundary.add(sphere(Vec3::new(0.0, 0.0, 7.0), 1.0));
+        let medium = medium(Arc::new(boundary), 0.3);
+        let ray = Ray::new(Vec3::all(0.0), Vec3::new(0.0, 0.0, 1.0));
+
+        // The gap between the spheres doesn't count.
+        let expected = f32::exp(-0.3 * 4.0);
+        assert!((medium.transmittance(&ray, 0.001, f32::INFINITY) - expected).abs() < 1e-3);
+        assert!((miss_fraction(&medium, &ray, f32::INFINITY) - expected).abs() < 0.01);
+
+        // Stopping in the gap only goes through the first sphere.
+        let expected = f32::exp(-0.3 * 2.0);
+        assert!((medium.transmittance(&ray, 0.001, 5.0) - expected).abs() < 1e-3);
+        assert!((miss_fraction(&medium, &ray, 5.0) - expected).abs() < 0.01);
+
+        for _ in 0..1000 {
+            if let Some(hit) = medium.hit(&ray, 0.001, f32::INFINITY) {
+                assert!((2.0..=4.0).contains(&hit.t) || (6.0..=8.0).contains(&hit.t), "hit at {} is in the gap", hit.t);
+            }
         }
+    }
 
-        let t = hit1.t + hit_distance / ray_length;
-        Some(HitRecord::new(
-            ray.at(t), 
-            &Ray::new(Vec3::all(0.0), Vec3::all(0.0)), // Arbitrary
-            Vec3::new(1.0, 0.0, 0.0), // Arbitrary
-            t, 
-            Vec2::all(0.0), // Arbitrary
-            self.phase_function.clone()))
+    #[test]
+    fn box_boundary() {
+        let material: Arc<dyn Material> = Arc::new(Isotropic::new(Arc::new(SolidColor::new(Vec3::all(1.0)))));
+        let boundary = Arc::new(AaBox::new(Vec3::new(-1.0, -1.0, 2.0), Vec3::new(1.0, 1.0, 5.0), material));
+        let medium = medium(boundary, 0.4);
+
+        // Going through the whole box along z.
+        let ray = Ray::new(Vec3::all(0.0), Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(medium.boundary.inside_intervals(&ray, 0.001, f32::INFINITY), vec![(2.0, 5.0)]);
+        let expected = f32::exp(-0.4 * 3.0);
+        assert!((medium.transmittance(&ray, 0.001, f32::INFINITY) - expected).abs() < 1e-3);
+        assert!((miss_fraction(&medium, &ray, f32::INFINITY) - expected).abs() < 0.01);
+
+        // Starting inside and leaving through the min side of x.
+        let ray = Ray::new(Vec3::new(0.5, 0.0, 3.0), Vec3::new(-1.0, 0.0, 0.0));
+        let expected = f32::exp(-0.4 * 1.5);
+        assert!((medium.transmittance(&ray, 0.001, f32::INFINITY) - expected).abs() < 1e-3);
+        assert!((miss_fraction(&medium, &ray, f32::INFINITY) - expected).abs() < 0.01);
     }
 
-    fn bounding_box(&self) -> Option<Aabb> {
-        self.boundary.bounding_box()
+    #[test]
+    fn rotated_box_boundary() {
+        let material: Arc<dyn Material> = Arc::new(Isotropic::new(Arc::new(SolidColor::new(Vec3::all(1.0)))));
+        let cube = Arc::new(AaBox::new(Vec3::all(-1.0), Vec3::all(1.0), material));
+        // Turned by 45 degrees the cube is a diamond reaching sqrt(2) from the center along x and z.
+        let boundary = Arc::new(Translate::new(Arc::new(RotateY::new(cube, PI / 4.0)), Vec3::new(0.0, 0.0, 5.0)));
+        let medium = medium(boundary, 0.4);
+        let bounds = medium.bounding_box().unwrap();
+        assert!((bounds.max.x - SQRT_2).abs() < 1e-4 && (bounds.min.z - (5.0 - SQRT_2)).abs() < 1e-4);
+
+        let ray = Ray::new(Vec3::all(0.0), Vec3::new(0.0, 0.0, 1.0));
+        let intervals = medium.boundary.inside_intervals(&ray, 0.001, f32::INFINITY);
+        assert_eq!(intervals.len(), 1);
+        assert!((intervals[0].0 - (5.0 - SQRT_2)).abs() < 1e-4 && (intervals[0].1 - (5.0 + SQRT_2)).abs() < 1e-4);
+        let expected = f32::exp(-0.4 * 2.0 * SQRT_2);
+        assert!((medium.transmittance(&ray, 0.001, f32::INFINITY) - expected).abs() < 1e-3);
+        assert!((miss_fraction(&medium, &ray, f32::INFINITY) - expected).abs() < 0.01);
+
+        // Starting at the center.
+        let ray = Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::new(1.0, 0.0, 0.0));
+        let expected = f32::exp(-0.4 * SQRT_2);
+        assert!((medium.transmittance(&ray, 0.001, f32::INFINITY) - expected).abs() < 1e-3);
+        assert!((miss_fraction(&medium, &ray, f32::INFINITY) - expected).abs() < 0.01);
+    }
+
+    #[test]
+    fn high_density() {
+        let medium = medium(sphere(Vec3::new(0.0, 0.0, 5.0), 1.0), 1000.0);
+        let ray = Ray::new(Vec3::all(0.0), Vec3::new(0.0, 0.0, 2.0));
+
+        assert!(medium.transmittance(&ray, 0.001, f32::INFINITY) < 1e-6);
+        // Every ray stops right after entering at t = 2.
+        for _ in 0..1000 {
+            let hit = medium.hit(&ray, 0.001, f32::INFINITY).expect("dense medium should always be hit");
+            assert!(hit.t >= 2.0 && hit.t < 2.01);
+        }
     }
-}
\ No newline at end of file
+}
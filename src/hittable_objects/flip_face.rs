@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use crate::{aabb::Aabb, ray::Ray, vec3::{Vec3, Pt3}};
+
+use super::{Hittable, HitRecord};
+
+// Swaps which side of the object is the front. Needed for one sided lights that face the opposite direction of the normal.
+pub struct FlipFace {
+    pub hittable: Arc<dyn Hittable>
+}
+
+impl FlipFace {
+    pub fn new(hittable: Arc<dyn Hittable>) -> Self {
+        Self { hittable }
+    }
+}
+
+impl Hittable for FlipFace {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let mut hit = self.hittable.hit(ray, t_min, t_max)?;
+        hit.is_front_face = !hit.is_front_face;
+        Some(hit)
+    }
+
+    fn transmittance(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        self.hittable.transmittance(ray, t_min, t_max)
+    }
+
+    fn pdf_value(&self, origin: Pt3, direction: Vec3) -> f32 {
+        self.hittable.pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: Pt3) -> Vec3 {
+        self.hittable.random(origin)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.hittable.bounding_box()
+    }
+}
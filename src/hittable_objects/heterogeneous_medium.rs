@@ -0,0 +1,91 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::{materials::Material, density_fields::{DensityField, VoxelGrid}, ray::Ray, aabb::Aabb, vec2::Vec2};
+
+use super::{Hittable, HitRecord, AaBox};
+
+// Medium inside of a boundary whose density changes from point to point, like smoke or clouds.
+pub struct HeterogeneousMedium {
+    pub boundary: Arc<dyn Hittable>,
+    pub phase_function: Arc<dyn Material>,
+    pub density: Arc<dyn DensityField>
+}
+
+impl HeterogeneousMedium {
+    pub fn new(boundary: Arc<dyn Hittable>, phase_function: Arc<dyn Material>, density: Arc<dyn DensityField>) -> Self {
+        Self { boundary, phase_function, density }
+    }
+
+    // Fills the box of the grid.
+    pub fn from_voxel_grid(grid: Arc<VoxelGrid>, phase_function: Arc<dyn Material>) -> Self {
+        let boundary = Arc::new(AaBox::new(grid.bounds.min, grid.bounds.max, phase_function.clone()));
+        Self::new(boundary, phase_function, grid)
+    }
+}
+
+impl Hittable for HeterogeneousMedium {
+    // Delta tracking (Woodcock tracking). The medium is filled with fictional particles up to a bound of the density so
+    // distances can be sampled like in a constant medium. At each collision the particle is real with probability
+    // density / bound, otherwise the ray continues as if nothing happened. Each segment of the ray can have its own bound.
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let ray_length = ray.direction.length();
+        for (t1, t2) in self.boundary.inside_intervals(ray, t_min, t_max) {
+            for (start, end, majorant) in self.density.majorants(ray, t1, t2) {
+                if majorant <= 0.0 {
+                    continue;
+                }
+                // Collisions don't remember anything so the sampling can start over at the start of each segment.
+                let mut t = start;
+                loop {
+                    t -= f32::ln(1.0 - rand::thread_rng().gen::<f32>()) / (majorant * ray_length);
+                    if t >= end {
+                        break;
+                    }
+                    let point = ray.at(t);
+                    if rand::thread_rng().gen::<f32>() * majorant < self.density.density(point) {
+                        // The normal doesn't mean anything inside of a medium. Pointing it against the ray keeps it on the front face.
+                        return Some(HitRecord::new(point, ray, -ray.direction / ray_length, t, Vec2::all(0.0), self.phase_function.clone()));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    // Ratio tracking. Steps through the same collisions as delta tracking, but instead of randomly stopping at a real one
+    // the transmittance is multiplied by the probability of the collision being fictional. Gives a fraction instead of
+    // all or nothing so shadows through smoke are a lot less noisy.
+    fn transmittance(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        let ray_length = ray.direction.length();
+        let mut transmittance = 1.0;
+        for (t1, t2) in self.boundary.inside_intervals(ray, t_min, t_max) {
+            for (start, end, majorant) in self.density.majorants(ray, t1, t2) {
+                if majorant <= 0.0 {
+                    continue;
+                }
+                let mut t = start;
+                loop {
+                    t -= f32::ln(1.0 - rand::thread_rng().gen::<f32>()) / (majorant * ray_length);
+                    if t >= end {
+                        break;
+                    }
+                    transmittance *= 1.0 - self.density.density(ray.at(t)) / majorant;
+                    // Continuing once almost nothing gets through is a waste. Russian roulette keeps the result unbiased.
+                    if transmittance < 0.1 {
+                        if rand::thread_rng().gen::<f32>() < 0.5 {
+                            return 0.0;
+                        }
+                        transmittance *= 2.0;
+                    }
+                }
+            }
+        }
+        transmittance
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+}
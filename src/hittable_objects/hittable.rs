@@ -1,26 +1,48 @@
 use crate::vec2::Vec2;
-use crate::vec3::Vec3;
+use crate::vec3::{Vec3, Pt3};
 use crate::ray::Ray;
 use crate::aabb::Aabb;
 use crate::materials::Material;
+use crate::lights::Light;
+use crate::onb::Onb;
 use std::option::Option;
 use std::sync::Arc;
 
-// #[derive(Clone)]
+#[derive(Clone)]
 pub struct HitRecord {
     pub point: Vec3,
     pub normal: Vec3,
+    // Derivatives of the point with respect to the texture coordinates. Not normalized, their length is how fast
+    // the point moves when the texture coordinate changes. They always describe the outward facing side of the surface.
+    pub tangent: Vec3,
+    pub bitangent: Vec3,
     pub t: f32,
+    // Width of the ray cone at the hit point.
+    pub cone_width: f32,
     pub is_front_face: bool,
     pub texture_coord: Vec2, // range <0, 1> going from bottom left.
-    pub material: Arc<dyn Material>
+    pub material: Arc<dyn Material>,
+    // Set when the object is also sampled as a light, so light found by scattered rays can be weighted against it.
+    pub light: Option<Arc<dyn Light>>
 }
 
 impl HitRecord {
     pub fn new(point: Vec3, ray: &Ray, outward_normal: Vec3, t: f32, texture_coord: Vec2, material: Arc<dyn Material>) -> HitRecord {
         let is_front_face = Vec3::dot(ray.direction, outward_normal) < 0.0;
         let normal = if is_front_face { outward_normal } else { -outward_normal };
-        HitRecord{ point, normal, t, is_front_face, texture_coord, material }
+        // Objects that don't have texture coordinates still get some consistent tangent frame.
+        let Onb { u: tangent, v: bitangent, .. } = Onb::from_w(outward_normal);
+        HitRecord{ point, normal, tangent, bitangent, t, cone_width: ray.cone_width_at(t), is_front_face, texture_coord, material, light: None }
+    }
+
+    pub fn with_tangents(self, tangent: Vec3, bitangent: Vec3) -> HitRecord {
+        HitRecord{ tangent, bitangent, ..self }
+    }
+
+    // Width of the ray cone in texture coordinates. Uses the direction in which the texture coordinates change
+    // the slowest so the texture is blurred rather than aliased.
+    pub fn texture_footprint(&self) -> f32 {
+        self.cone_width / f32::min(self.tangent.length(), self.bitangent.length())
     }
 }
 
@@ -28,4 +50,49 @@ pub trait Hittable where Self: Send + Sync {
     fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord>;
     // Optional because for example infinite shapes like planes don't have an AABB.
     fn bounding_box(&self) -> Option<Aabb>;
-}
\ No newline at end of file
+
+    // Fraction of light that makes it through the object between t_min and t_max. Used by shadow rays.
+    // Solid objects block all of it when they are hit, media let part of it through.
+    fn transmittance(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        if self.hit(ray, t_min, t_max).is_some() { 0.0 } else { 1.0 }
+    }
+
+    // Probability density of `random` choosing the direction from the origin, per unit of solid angle.
+    // Only shapes that can be used as area lights need it, the rest are never chosen.
+    fn pdf_value(&self, _origin: Pt3, _direction: Vec3) -> f32 {
+        0.0
+    }
+
+    // Random direction from the origin towards the object.
+    fn random(&self, _origin: Pt3) -> Vec3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
+
+    // Parts of the ray between t_min and t_max that are inside of the object. Found by walking through all the hits
+    // and using which side each one is on, so rays starting inside and shapes the ray enters multiple times both work.
+    // The object should be closed. If it isn't and the ray doesn't leave, the last part goes up to t_max.
+    fn inside_intervals(&self, ray: &Ray, t_min: f32, t_max: f32) -> Vec<(f32, f32)> {
+        // Hits closer together than this are treated as the same crossing.
+        const STEP: f32 = 0.0001;
+        let mut intervals = Vec::new();
+        let mut entry: Option<f32> = None;
+        let mut t = t_min;
+        let mut first = true;
+        while let Some(hit) = self.hit(ray, t, t_max) {
+            if hit.is_front_face {
+                entry.get_or_insert(hit.t);
+            } else if let Some(start) = entry.take() {
+                intervals.push((start, hit.t));
+            } else if first {
+                // Leaving before entering means the ray started inside.
+                intervals.push((t_min, hit.t));
+            }
+            first = false;
+            t = hit.t + STEP;
+        }
+        if let Some(start) = entry {
+            intervals.push((start, t_max));
+        }
+        intervals
+    }
+}
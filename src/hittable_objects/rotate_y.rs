@@ -20,8 +20,8 @@ impl RotateY {
             None => return RotateY{ hittable, sin, cos, aabb: None }
         };
 
-        let mut max = Pt3::all(f32::INFINITY);
-        let mut min = Pt3::all(-f32::INFINITY);
+        let mut min = Pt3::all(f32::INFINITY);
+        let mut max = Pt3::all(-f32::INFINITY);
 
         for i in 0..2 {
             for j in 0..2 {
@@ -32,7 +32,7 @@ impl RotateY {
                     let z = k * aabb.max.z + (1.0 - k)* aabb.min.z;
 
                     let new_x = cos * x + sin * z;
-                    let new_z = -cos * x + cos * z;
+                    let new_z = -sin * x + cos * z;
 
                     let tester = Vec3::new(new_x, y, new_z);
 
@@ -44,12 +44,11 @@ impl RotateY {
             }
         }
 
-        RotateY{ hittable, sin, cos, aabb: Some(aabb) }
+        RotateY{ hittable, sin, cos, aabb: Some(Aabb::new(min, max)) }
     }
-}
 
-impl Hittable for RotateY {
-    fn hit(&self, Ray{ origin, direction }: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+    fn to_object_space(&self, ray: &Ray) -> Ray {
+        let Ray { origin, direction, .. } = *ray;
         let origin = Vec3::new(
             self.cos * origin.x - self.sin * origin.z,
             origin.y,
@@ -60,22 +59,46 @@ impl Hittable for RotateY {
             direction.y,
             self.sin * direction.x + self.cos * direction.z);
 
-        let ray = Ray::new(origin, direction);
+        Ray { origin, direction, ..*ray }
+    }
+
+    fn to_world_space(&self, v: Vec3) -> Vec3 {
+        Vec3::new(
+            self.cos * v.x + self.sin * v.z,
+            v.y,
+            -self.sin * v.x + self.cos * v.z)
+    }
+}
 
-        let HitRecord { point, normal, t, texture_coord, material, .. } 
-            = self.hittable.hit(&ray, t_min, t_max)?;
+impl Hittable for RotateY {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let ray = self.to_object_space(ray);
+
+        let hit = self.hittable.hit(&ray, t_min, t_max)?;
+
+        // Rotating keeps the angles between the vectors and the ray so the side that was hit stays the same.
+        // Building a new record from the rotated normal and the object space ray would mix up the two spaces.
+        Some(HitRecord {
+            point: self.to_world_space(hit.point),
+            normal: self.to_world_space(hit.normal),
+            tangent: self.to_world_space(hit.tangent),
+            bitangent: self.to_world_space(hit.bitangent),
+            ..hit
+        })
+    }
 
-        let point = Vec3::new(
-            self.cos * point.x + self.sin * point.z,
-            point.y,
-            -self.sin * point.x + self.cos * point.z);
+    fn transmittance(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        self.hittable.transmittance(&self.to_object_space(ray), t_min, t_max)
+    }
 
-        let normal = Vec3::new(
-            self.cos * normal.x + self.sin * normal.z,
-            normal.y,
-            -self.sin * normal.x + self.cos * normal.z).normalized();
+    fn pdf_value(&self, origin: Pt3, direction: Vec3) -> f32 {
+        let ray = self.to_object_space(&Ray::new(origin, direction));
+        self.hittable.pdf_value(ray.origin, ray.direction)
+    }
 
-        Some(HitRecord::new(point, &ray, normal, t, texture_coord, material))
+    fn random(&self, origin: Pt3) -> Vec3 {
+        let origin = self.to_object_space(&Ray::new(origin, Vec3::all(0.0))).origin;
+        self.to_world_space(self.hittable.random(origin))
     }
 
     fn bounding_box(&self) -> Option<Aabb> {
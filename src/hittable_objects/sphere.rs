@@ -5,8 +5,11 @@ use crate::aabb::Aabb;
 use crate::hittable_objects::{Hittable, HitRecord};
 use crate::materials::Material;
 use crate::vec2::Vec2;
-use crate::vec3::Vec3;
+use crate::vec3::{Vec3, Pt3};
 use crate::ray::Ray;
+use crate::onb::Onb;
+
+use rand::Rng;
 
 pub struct Sphere {
     pub center: Vec3,
@@ -48,6 +51,21 @@ impl Sphere {
 
         Vec2::new(phi / (TAU), theta / PI)
     }
+
+    // Derivatives of the point with respect to u and v found by differentiating the parametric equation.
+    // The sin(theta) factor appears because the circles of constant theta get smaller near the poles.
+    fn tangents(&self, outward_normal: Vec3) -> Option<(Vec3, Vec3)> {
+        let n = &outward_normal;
+        let sin_theta = f32::sqrt(n.x * n.x + n.z * n.z);
+        // At the poles u doesn't change the point so there is no tangent.
+        if sin_theta < 1e-6 {
+            return None;
+        }
+        let around = Vec3::new(n.z, 0.0, -n.x) / sin_theta;
+        let tangent = TAU * self.radius * sin_theta * around;
+        let bitangent = PI * self.radius * Vec3::cross(outward_normal, around);
+        Some((tangent, bitangent))
+    }
 }
 
 impl Hittable for Sphere {
@@ -82,10 +100,13 @@ impl Hittable for Sphere {
 
         let sqrt_discriminant = discriminant.sqrt();
 
-        let root = (-half_b - sqrt_discriminant) / a;
-
+        // Trying the nearer root first. If it's out of range the ray might have started inside so the far one is checked too.
+        let mut root = (-half_b - sqrt_discriminant) / a;
         if (root < t_min) || (root > t_max) {
-            return None
+            root = (-half_b + sqrt_discriminant) / a;
+            if (root < t_min) || (root > t_max) {
+                return None
+            }
         }
 
         let point = ray.at(root);
@@ -95,7 +116,11 @@ impl Hittable for Sphere {
         
         let uv = Self::texture_coord(outward_normal);
 
-        Some(HitRecord::new(point, ray, outward_normal, root, uv, self.material.clone()))
+        let hit_record = HitRecord::new(point, ray, outward_normal, root, uv, self.material.clone());
+        match self.tangents(outward_normal) {
+            Some((tangent, bitangent)) => Some(hit_record.with_tangents(tangent, bitangent)),
+            None => Some(hit_record)
+        }
     }
 
     fn bounding_box(&self) -> Option<Aabb> {
@@ -103,4 +128,34 @@ impl Hittable for Sphere {
             self.center - Vec3::all(self.radius), 
             self.center + Vec3::all(self.radius)))
     }
+
+    // Directions are chosen uniformly inside of the cone the sphere covers when seen from the origin.
+    fn pdf_value(&self, origin: Pt3, direction: Vec3) -> f32 {
+        if self.hit(&Ray::new(origin, direction), 0.001, f32::INFINITY).is_none() {
+            return 0.0;
+        }
+        let distance_squared = (self.center - origin).length_squared();
+        let radius_squared = self.radius * self.radius;
+        // From inside every direction hits the sphere.
+        if distance_squared <= radius_squared {
+            return 1.0 / (4.0 * PI);
+        }
+        let cos_max = f32::sqrt(1.0 - radius_squared / distance_squared);
+        1.0 / (TAU * (1.0 - cos_max))
+    }
+
+    fn random(&self, origin: Pt3) -> Vec3 {
+        let to_center = self.center - origin;
+        let distance_squared = to_center.length_squared();
+        let radius_squared = self.radius * self.radius;
+        if distance_squared <= radius_squared {
+            return Vec3::random_unit();
+        }
+        let (r1, r2): (f32, f32) = (rand::thread_rng().gen(), rand::thread_rng().gen());
+        let cos_max = f32::sqrt(1.0 - radius_squared / distance_squared);
+        let cos = 1.0 - r1 * (1.0 - cos_max);
+        let sin = f32::sqrt(f32::max(0.0, 1.0 - cos * cos));
+        let phi = TAU * r2;
+        Onb::from_w(to_center).local(Vec3::new(sin * phi.cos(), sin * phi.sin(), cos))
+    }
 }
\ No newline at end of file
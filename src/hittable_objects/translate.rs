@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use crate::{vec3::Vec3, aabb::Aabb, ray::Ray};
+use crate::{vec3::{Vec3, Pt3}, aabb::Aabb, ray::Ray};
 
 use super::{Hittable, HitRecord};
 
@@ -17,7 +17,7 @@ impl Translate {
 
 impl Hittable for Translate {
     fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
-        let ray = Ray::new(ray.origin - self.translation, ray.direction);
+        let ray = Ray { origin: ray.origin - self.translation, ..*ray };
         match self.hittable.hit(&ray, t_min, t_max) {
             Some(mut hit) => {
                 hit.point += self.translation;
@@ -27,6 +27,19 @@ impl Hittable for Translate {
         }
     }
 
+    fn transmittance(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        let ray = Ray { origin: ray.origin - self.translation, ..*ray };
+        self.hittable.transmittance(&ray, t_min, t_max)
+    }
+
+    fn pdf_value(&self, origin: Pt3, direction: Vec3) -> f32 {
+        self.hittable.pdf_value(origin - self.translation, direction)
+    }
+
+    fn random(&self, origin: Pt3) -> Vec3 {
+        self.hittable.random(origin - self.translation)
+    }
+
     fn bounding_box(&self) -> Option<Aabb> {
         match self.hittable.bounding_box() {
             Some(aabb) => Some(Aabb::new(aabb.min + self.translation, aabb.max + self.translation)),
@@ -0,0 +1,35 @@
+mod light;
+pub use light::*;
+
+mod light_bounds;
+pub use light_bounds::*;
+
+mod point_light;
+pub use point_light::*;
+
+mod ies_profile;
+pub use ies_profile::*;
+
+mod spot_light;
+pub use spot_light::*;
+
+mod directional_light;
+pub use directional_light::*;
+
+mod sphere_light;
+pub use sphere_light::*;
+
+mod area_light;
+pub use area_light::*;
+
+mod light_sampler;
+pub use light_sampler::*;
+
+mod uniform_light_sampler;
+pub use uniform_light_sampler::*;
+
+mod power_light_sampler;
+pub use power_light_sampler::*;
+
+mod light_bvh;
+pub use light_bvh::*;
@@ -0,0 +1,135 @@
+use std::f32::consts::PI;
+use std::sync::{Arc, Weak};
+
+use crate::{hittable_objects::{Hittable, HitRecord}, vec3::{Vec3, Pt3}, ray::Ray, aabb::Aabb};
+
+use super::{Light, LightSample, LightBounds};
+
+// Makes an emissive object like a sphere or a rect with DiffuseLight a light as well. Rays still hit it like any
+// other object but it's also sampled with shadow rays, so small bright objects aren't only found by chance.
+// The same Arc has to be added both to the objects and to the lights of the scene.
+pub struct AreaLight {
+    pub shape: Arc<dyn Hittable>,
+    // Hits are tagged with the light so the raytracer knows which light a scattered ray found.
+    this: Weak<AreaLight>
+}
+
+impl AreaLight {
+    pub fn new(shape: Arc<dyn Hittable>) -> Arc<Self> {
+        Arc::new_cyclic(|this| Self { shape, this: this.clone() })
+    }
+}
+
+impl Hittable for AreaLight {
+    fn hit(&self, ray: &Ray, t_min: f32, t_max: f32) -> Option<HitRecord> {
+        let mut hit = self.shape.hit(ray, t_min, t_max)?;
+        hit.light = self.this.upgrade().map(|light| light as Arc<dyn Light>);
+        Some(hit)
+    }
+
+    fn transmittance(&self, ray: &Ray, t_min: f32, t_max: f32) -> f32 {
+        self.shape.transmittance(ray, t_min, t_max)
+    }
+
+    fn pdf_value(&self, origin: Pt3, direction: Vec3) -> f32 {
+        self.shape.pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: Pt3) -> Vec3 {
+        self.shape.random(origin)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.shape.bounding_box()
+    }
+}
+
+impl Light for AreaLight {
+    fn sample(&self, point: Pt3) -> Option<LightSample> {
+        let direction = self.shape.random(point);
+        let pdf = self.shape.pdf_value(point, direction);
+        if pdf <= 0.0 {
+            return None;
+        }
+        // The emitted light can depend on where and from which side the shape is hit.
+        let hit = self.shape.hit(&Ray::new(point, direction), 0.001, f32::INFINITY)?;
+        let emitted = hit.material.color_emmited(&hit);
+        if emitted.is_near_zero() {
+            return None;
+        }
+        let length = direction.length();
+        Some(LightSample { direction: direction / length, distance: hit.t * length, radiance: emitted / pdf })
+    }
+
+    fn pdf(&self, point: Pt3, direction: Vec3) -> f32 {
+        self.shape.pdf_value(point, direction)
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        let bounds = self.shape.bounding_box()?;
+        let center = 0.5 * (bounds.min + bounds.max);
+        let size = bounds.max - bounds.min;
+
+        // The material only gives the emitted light at a hit, so it's estimated with rays shot at the center
+        // from all six sides. Taking the brightest one keeps one sided lights from being underestimated.
+        let mut radiance: f32 = 0.0;
+        for axis in 0..3 {
+            for side in [-1.0, 1.0] {
+                let mut offset = Vec3::all(0.0);
+                offset[axis] = side * (size[axis] + 1.0);
+                if let Some(hit) = self.shape.hit(&Ray::new(center + offset, -offset), 0.0, f32::INFINITY) {
+                    let emitted = hit.material.color_emmited(&hit);
+                    radiance = radiance.max((emitted.x + emitted.y + emitted.z) / 3.0);
+                }
+            }
+        }
+
+        // A diffuse emitter sends pi times its radiance out of every unit of area. Half of the surface of the bounds
+        // is close to the area of both spheres and rects.
+        let area = size.x * size.y + size.y * size.z + size.z * size.x;
+        Some(LightBounds::omnidirectional(bounds, PI * area * radiance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hittable_objects::{Sphere, XzRect, FlipFace};
+    use crate::materials::{Material, DiffuseLight};
+    use crate::textures::SolidColor;
+    use crate::vec3::Color;
+
+    const SAMPLES: usize = 200_000;
+
+    // Solid angle found by checking how many uniformly chosen directions hit the light.
+    fn solid_angle(light: &AreaLight, point: Pt3) -> f32 {
+        let hits = (0..SAMPLES).filter(|_| light.hit(&Ray::new(point, Vec3::random_unit()), 0.001, f32::INFINITY).is_some()).count();
+        4.0 * PI * hits as f32 / SAMPLES as f32
+    }
+
+    // The samples are divided by their probability so on average they add up to the radiance times the solid angle.
+    fn average_radiance(light: &AreaLight, point: Pt3) -> f32 {
+        let total: f32 = (0..SAMPLES).filter_map(|_| light.sample(point)).map(|sample| {
+            assert!((light.pdf(point, sample.direction) * sample.radiance.x - 2.0).abs() < 1e-3);
+            sample.radiance.x
+        }).sum();
+        total / SAMPLES as f32
+    }
+
+    #[test]
+    fn sphere_and_rect_samples() {
+        let material: Arc<dyn Material> = Arc::new(DiffuseLight::new(Arc::new(SolidColor::new(Color::all(2.0)))));
+        let sphere = AreaLight::new(Arc::new(Sphere::new(Pt3::new(0.0, 3.0, 0.0), 1.0, material.clone())));
+        let rect = AreaLight::new(Arc::new(FlipFace::new(Arc::new(XzRect::new(-1.0, 2.0, -0.5, 1.5, 2.0, material)))));
+        let point = Pt3::new(0.5, 0.0, 0.0);
+
+        for light in [sphere, rect] {
+            // Both estimates are off by about 1.5% so this allows three standard deviations.
+            let expected = 2.0 * solid_angle(&light, point);
+            assert!((average_radiance(&light, point) - expected).abs() < 0.05 * expected);
+            // Hits are tagged so the raytracer can weight them against the light's samples.
+            let hit = light.hit(&Ray::new(point, Vec3::new(0.0, 1.0, 0.0)), 0.001, f32::INFINITY).unwrap();
+            assert!(hit.light.is_some());
+        }
+    }
+}
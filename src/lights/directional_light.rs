@@ -0,0 +1,27 @@
+use crate::vec3::{Vec3, Color};
+
+use super::{Light, LightSample, LightBounds};
+
+// Light coming from infinitely far away in a single direction, like the sun.
+pub struct DirectionalLight {
+    // The direction the light travels in.
+    pub direction: Vec3,
+    // Power arriving per unit of area perpendicular to the direction.
+    pub irradiance: Color
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vec3, irradiance: Color) -> Self {
+        Self { direction: direction.normalized(), irradiance }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample(&self, _point: Vec3) -> Option<LightSample> {
+        Some(LightSample { direction: -self.direction, distance: f32::INFINITY, radiance: self.irradiance })
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        None
+    }
+}
@@ -0,0 +1,211 @@
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub struct IesLoadError {
+    pub path: PathBuf,
+    pub cause: Error
+}
+
+impl std::fmt::Display for IesLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to load IES profile {}: {}", self.path.display(), self.cause)
+    }
+}
+
+impl std::error::Error for IesLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.cause)
+    }
+}
+
+// Light distribution of a real fixture measured by the manufacturer and stored in the IES LM-63 format.
+// Only type C photometry is supported, which is what almost all files use. Vertical angles go from the direction
+// the light points in and horizontal angles go around it.
+pub struct IesProfile {
+    // Both in radians and increasing.
+    vertical_angles: Vec<f32>,
+    horizontal_angles: Vec<f32>,
+    // One row of vertical values per horizontal angle. Scaled so the brightest value is 1.
+    candela: Vec<Vec<f32>>
+}
+
+impl IesProfile {
+    pub fn from_file(path: &Path) -> Result<Self, IesLoadError> {
+        std::fs::read_to_string(path)
+            .and_then(|text| Self::parse(&text))
+            .map_err(|cause| IesLoadError { path: path.to_path_buf(), cause })
+    }
+
+    pub fn parse(text: &str) -> Result<Self, Error> {
+        let invalid = |message: &str| Error::new(ErrorKind::InvalidData, message.to_string());
+
+        // Everything before the TILT line is keywords describing the fixture.
+        let mut lines = text.lines();
+        let tilt = lines.by_ref()
+            .find_map(|line| line.trim().strip_prefix("TILT="))
+            .ok_or_else(|| invalid("missing TILT line"))?;
+        let mut numbers = lines
+            .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+            .filter(|token| !token.is_empty())
+            .map(|token| token.parse::<f32>().map_err(|_| invalid(&format!("invalid number {}", token))));
+        let mut next = || numbers.next().unwrap_or_else(|| Err(invalid("unexpected end of file")));
+
+        match tilt.trim() {
+            "NONE" => (),
+            // The tilt data changes the output when the fixture is rotated. The profile is used as measured so it's skipped.
+            "INCLUDE" => {
+                next()?;
+                let count = next()? as usize;
+                for _ in 0..(2 * count) {
+                    next()?;
+                }
+            },
+            _ => return Err(invalid("tilt data in a separate file is not supported"))
+        }
+
+        let _lamp_count = next()?;
+        let _lumens_per_lamp = next()?;
+        let _multiplier = next()?;
+        let vertical_count = next()? as usize;
+        let horizontal_count = next()? as usize;
+        let photometric_type = next()?;
+        // Units, width, length, height, ballast factor, future use and input watts.
+        for _ in 0..7 {
+            next()?;
+        }
+        if photometric_type != 1.0 {
+            return Err(invalid("only type C photometry is supported"));
+        }
+        if vertical_count == 0 || horizontal_count == 0 {
+            return Err(invalid("profile has no angles"));
+        }
+
+        let mut read_angles = |count: usize| (0..count).map(|_| next().map(f32::to_radians)).collect::<Result<Vec<f32>, Error>>();
+        let vertical_angles = read_angles(vertical_count)?;
+        let horizontal_angles = read_angles(horizontal_count)?;
+        let mut candela = Vec::with_capacity(horizontal_count);
+        for _ in 0..horizontal_count {
+            candela.push((0..vertical_count).map(|_| next()).collect::<Result<Vec<f32>, Error>>()?);
+        }
+
+        let max = candela.iter().flatten().fold(0.0f32, |max, &value| max.max(value));
+        if max > 0.0 {
+            for value in candela.iter_mut().flatten() {
+                *value /= max;
+            }
+        }
+        Ok(Self { vertical_angles, horizontal_angles, candela })
+    }
+
+    // Finds the index of the angle before the value and how far the value is towards the next angle.
+    fn locate(angles: &[f32], value: f32) -> (usize, f32) {
+        if angles.len() == 1 || value <= angles[0] {
+            return (0, 0.0);
+        }
+        let last = angles.len() - 1;
+        if value >= angles[last] {
+            return (last, 0.0);
+        }
+        let i = angles.partition_point(|&angle| angle <= value) - 1;
+        (i, (value - angles[i]) / (angles[i + 1] - angles[i]))
+    }
+
+    // Relative intensity in the direction. Both angles are in radians.
+    pub fn value(&self, vertical: f32, horizontal: f32) -> f32 {
+        use std::f32::consts::{PI, TAU};
+
+        // Outside of the measured vertical range the fixture doesn't emit any light.
+        let (first, last) = (self.vertical_angles[0], self.vertical_angles[self.vertical_angles.len() - 1]);
+        if vertical < first || vertical > last {
+            return 0.0;
+        }
+
+        // Symmetric fixtures only store part of the horizontal angles. The last angle says which symmetry is used.
+        let horizontal = horizontal.rem_euclid(TAU);
+        let last_horizontal = self.horizontal_angles[self.horizontal_angles.len() - 1];
+        let horizontal = if last_horizontal <= 0.0 {
+            0.0
+        } else if last_horizontal <= PI / 2.0 + 1e-3 {
+            let h = horizontal.rem_euclid(PI);
+            if h > PI / 2.0 { PI - h } else { h }
+        } else if last_horizontal <= PI + 1e-3 {
+            if horizontal > PI { TAU - horizontal } else { horizontal }
+        } else {
+            horizontal
+        };
+
+        let (v, tv) = Self::locate(&self.vertical_angles, vertical);
+        let (h, th) = Self::locate(&self.horizontal_angles, horizontal);
+        let v1 = usize::min(v + 1, self.vertical_angles.len() - 1);
+        let h1 = usize::min(h + 1, self.horizontal_angles.len() - 1);
+        let row = |h: usize| (1.0 - tv) * self.candela[h][v] + tv * self.candela[h][v1];
+        (1.0 - th) * row(h) + th * row(h1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Three vertical and three horizontal angles. The horizontal ones stop at 90 degrees so the other
+    // quadrants are mirrored.
+    const FIXTURE: &str = "IESNA:LM-63-2002
+[TEST] small fixture
+[MANUFAC] none
+TILT=NONE
+1 1000 1 3 3 1 1 0.1 0.1 0
+1 1 100
+0 45 90
+0 45 90
+100 50 0
+80, 40, 0
+60 30 0
+";
+
+    fn approx(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn parse_small_fixture() {
+        let profile = IesProfile::parse(FIXTURE).unwrap();
+        let degrees = |angle: f32| angle.to_radians();
+
+        // Values are scaled by the brightest one.
+        assert!(approx(profile.value(0.0, 0.0), 1.0));
+        assert!(approx(profile.value(degrees(45.0), 0.0), 0.5));
+        assert!(approx(profile.value(degrees(45.0), degrees(45.0)), 0.4));
+        assert!(approx(profile.value(degrees(45.0), degrees(90.0)), 0.3));
+        // Between the measured angles the values are interpolated.
+        assert!(approx(profile.value(degrees(22.5), 0.0), 0.75));
+        assert!(approx(profile.value(degrees(45.0), degrees(67.5)), 0.35));
+        // Mirrored into the first quadrant.
+        assert!(approx(profile.value(degrees(45.0), degrees(135.0)), 0.4));
+        assert!(approx(profile.value(degrees(45.0), degrees(180.0)), 0.5));
+        assert!(approx(profile.value(degrees(45.0), degrees(270.0)), 0.3));
+        // No light outside of the measured vertical angles.
+        assert_eq!(profile.value(degrees(100.0), 0.0), 0.0);
+    }
+
+    #[test]
+    fn tilt_data_is_skipped() {
+        let text = FIXTURE.replace("TILT=NONE", "TILT=INCLUDE\n1\n2\n0 90\n1.0 0.9");
+        let profile = IesProfile::parse(&text).unwrap();
+        assert!(approx(profile.value(std::f32::consts::FRAC_PI_4, 0.0), 0.5));
+    }
+
+    #[test]
+    fn invalid_files() {
+        let truncated = &FIXTURE[..FIXTURE.len() - 8];
+        assert_eq!(IesProfile::parse(truncated).err().unwrap().kind(), ErrorKind::InvalidData);
+        assert!(IesProfile::parse(&FIXTURE.replace("TILT=NONE\n", "")).is_err());
+        // Type B photometry.
+        assert!(IesProfile::parse(&FIXTURE.replace("1 1000 1 3 3 1", "1 1000 1 3 3 2")).is_err());
+        assert!(IesProfile::parse(&FIXTURE.replace("80, 40", "80, x")).is_err());
+
+        let error = IesProfile::from_file(Path::new("missing.ies")).err().unwrap();
+        assert_eq!(error.path, Path::new("missing.ies"));
+        assert_eq!(error.cause.kind(), ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,30 @@
+use crate::vec3::{Vec3, Color, Pt3};
+
+use super::LightBounds;
+
+#[derive(Debug, Clone, Copy)]
+pub struct LightSample {
+    // Normalized direction from the lit point towards the light.
+    pub direction: Vec3,
+    // Distance to the light along the direction. Infinity for lights that are infinitely far away.
+    pub distance: f32,
+    // Light arriving at the point from the direction divided by the probability of choosing it.
+    pub radiance: Color
+}
+
+// Light sampled directly from every lit point, which is called next event estimation. Most lights aren't part of
+// the scene's objects so rays can't hit them and sampling is the only way they contribute. Point, spot and
+// directional lights are infinitely small so a scattered ray would never hit them anyway. AreaLight is the exception.
+pub trait Light where Self: Send + Sync {
+    // Returns None when the light doesn't reach the point.
+    fn sample(&self, point: Vec3) -> Option<LightSample>;
+
+    // Probability density of `sample` choosing the direction, per unit of solid angle. Only needed by lights that
+    // scattered rays can hit too, to weight the two ways of finding them. Lights that can only be sampled return 0.
+    fn pdf(&self, _point: Pt3, _direction: Vec3) -> f32 {
+        0.0
+    }
+
+    // Used to choose which lights to sample. None for lights infinitely far away.
+    fn bounds(&self) -> Option<LightBounds>;
+}
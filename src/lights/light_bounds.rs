@@ -0,0 +1,125 @@
+use std::f32::consts::PI;
+
+use crate::{aabb::Aabb, vec3::{Vec3, Pt3}};
+
+// Conservative description of where a light is and which way it shines, used to estimate how much it can contribute
+// to a point without sampling it (Conty Estevez and Kulla, "Importance Sampling of Many Lights on the GPU").
+// The light emits into directions within theta_o of the direction, and each of those directions can spread light
+// up to theta_e further, like the hemisphere around the normal of a flat light.
+#[derive(Debug, Clone, Copy)]
+pub struct LightBounds {
+    pub bounds: Aabb,
+    // Total emitted power, the average of the channels.
+    pub power: f32,
+    pub direction: Vec3,
+    // Both in radians.
+    pub theta_o: f32,
+    pub theta_e: f32
+}
+
+impl LightBounds {
+    pub fn new(bounds: Aabb, power: f32, direction: Vec3, theta_o: f32, theta_e: f32) -> Self {
+        Self { bounds, power, direction: direction.normalized(), theta_o, theta_e }
+    }
+
+    // Light shining equally in all directions.
+    pub fn omnidirectional(bounds: Aabb, power: f32) -> Self {
+        Self::new(bounds, power, Vec3::new(0.0, 0.0, 1.0), PI, PI / 2.0)
+    }
+
+    pub fn centroid(&self) -> Pt3 {
+        0.5 * (self.bounds.min + self.bounds.max)
+    }
+
+    pub fn combined(&self, other: &LightBounds) -> LightBounds {
+        if self.power == 0.0 {
+            return *other;
+        }
+        if other.power == 0.0 {
+            return *self;
+        }
+        let (direction, theta_o) = Self::combined_cone(self.direction, self.theta_o, other.direction, other.theta_o);
+        LightBounds {
+            bounds: self.bounds.combined(&other.bounds),
+            power: self.power + other.power,
+            direction,
+            theta_o,
+            theta_e: f32::max(self.theta_e, other.theta_e)
+        }
+    }
+
+    // Smallest cone containing both cones.
+    fn combined_cone(a: Vec3, theta_a: f32, b: Vec3, theta_b: f32) -> (Vec3, f32) {
+        let theta_d = f32::acos(Vec3::dot(a, b).clamp(-1.0, 1.0));
+        if f32::min(theta_d + theta_b, PI) <= theta_a {
+            return (a, theta_a);
+        }
+        if f32::min(theta_d + theta_a, PI) <= theta_b {
+            return (b, theta_b);
+        }
+
+        let theta_o = (theta_a + theta_d + theta_b) / 2.0;
+        let axis = Vec3::cross(a, b);
+        if theta_o >= PI || axis.is_near_zero() {
+            return (a, PI);
+        }
+        // Rotating a towards b so the new cone touches the far sides of both (Rodrigues' rotation formula).
+        let axis = axis.normalized();
+        let (sin, cos) = (theta_o - theta_a).sin_cos();
+        let direction = cos * a + sin * Vec3::cross(axis, a) + (1.0 - cos) * Vec3::dot(axis, a) * axis;
+        (direction.normalized(), theta_o)
+    }
+
+    // Cosine of max(0, angle a - angle b) from their sines and cosines.
+    fn cos_subtract_clamped(sin_a: f32, cos_a: f32, sin_b: f32, cos_b: f32) -> f32 {
+        if cos_a > cos_b { 1.0 } else { cos_a * cos_b + sin_a * sin_b }
+    }
+
+    fn sin_subtract_clamped(sin_a: f32, cos_a: f32, sin_b: f32, cos_b: f32) -> f32 {
+        if cos_a > cos_b { 0.0 } else { sin_a * cos_b - cos_a * sin_b }
+    }
+
+    // Upper bound of the light arriving at the point from anything inside the bounds.
+    pub fn importance(&self, point: Pt3) -> f32 {
+        let center = self.centroid();
+        let radius_squared = (0.5 * (self.bounds.max - self.bounds.min)).length_squared();
+        // Points close to the bounds would get huge values so the distance is clamped to the size of the bounds.
+        let distance_squared = f32::max((point - center).length_squared(), radius_squared.sqrt());
+
+        // Angle between the direction of the light and the direction towards the point.
+        let to_point = (point - center).normalized();
+        let cos_w = Vec3::dot(self.direction, to_point);
+        let sin_w = f32::sqrt(f32::max(0.0, 1.0 - cos_w * cos_w));
+
+        // Angle of the cone the bounds cover when seen from the point. Inside the bounds it covers everything.
+        let (sin_b, cos_b) = if (point - center).length_squared() < radius_squared {
+            (0.0, -1.0)
+        } else {
+            let sin2 = radius_squared / (point - center).length_squared();
+            (sin2.sqrt(), f32::sqrt(f32::max(0.0, 1.0 - sin2)))
+        };
+
+        // Smallest angle between any direction of the emission cone and any direction towards the point.
+        let (sin_o, cos_o) = self.theta_o.sin_cos();
+        let cos_x = Self::cos_subtract_clamped(sin_w, cos_w, sin_o, cos_o);
+        let sin_x = Self::sin_subtract_clamped(sin_w, cos_w, sin_o, cos_o);
+        let cos_p = Self::cos_subtract_clamped(sin_x, cos_x, sin_b, cos_b);
+        if cos_p <= self.theta_e.cos() {
+            return 0.0;
+        }
+        self.power * cos_p / distance_squared
+    }
+
+    // How expensive it is to put the lights into one node when building the tree. Measures how much space and
+    // how many directions they cover, so lights that are close together and point the same way get grouped.
+    pub fn cost(&self) -> f32 {
+        let theta_w = f32::min(self.theta_o + self.theta_e, PI);
+        let (sin_o, cos_o) = self.theta_o.sin_cos();
+        let solid_angle = 2.0 * PI * (1.0 - cos_o)
+            + PI / 2.0 * (2.0 * theta_w * sin_o - f32::cos(self.theta_o - 2.0 * theta_w) - 2.0 * self.theta_o * sin_o + cos_o);
+        let size = self.bounds.max - self.bounds.min;
+        let surface_area = 2.0 * (size.x * size.y + size.y * size.z + size.z * size.x);
+        // Points still have to be ordered somehow.
+        self.power * solid_angle * f32::max(surface_area, 1e-6)
+    }
+}
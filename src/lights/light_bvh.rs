@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::vec3::Pt3;
+
+use super::{Light, LightSampler, LightBounds, light_key};
+
+enum LightBvhNode {
+    Leaf { bounds: LightBounds, light: usize },
+    // Indices of the children in the list of nodes.
+    Interior { bounds: LightBounds, children: [usize; 2] }
+}
+
+impl LightBvhNode {
+    fn bounds(&self) -> &LightBounds {
+        match self {
+            LightBvhNode::Leaf { bounds, .. } | LightBvhNode::Interior { bounds, .. } => bounds
+        }
+    }
+}
+
+// Tree of lights grouped by position and direction (Conty Estevez and Kulla). Sampling walks down from the root choosing
+// a child proportionally to an estimate of how much it can contribute to the point, based on power, distance and
+// whether the point is in the direction the lights shine. Nearby lights get most of the samples even in huge scenes.
+pub struct LightBvh {
+    lights: Vec<Arc<dyn Light>>,
+    // Lights without bounds can't be put into the tree so they are chosen uniformly.
+    infinite_lights: Vec<usize>,
+    nodes: Vec<LightBvhNode>,
+    // Parent of each node, so the probability of reaching a leaf can be found without sampling.
+    parents: Vec<Option<usize>>,
+    // Leaf node of each light, or None for infinite lights.
+    keys: HashMap<usize, Option<usize>>,
+    root: Option<usize>
+}
+
+impl LightBvh {
+    pub fn new(lights: Vec<Arc<dyn Light>>) -> Self {
+        let mut infinite_lights = Vec::new();
+        let mut bounded = Vec::new();
+        for (i, light) in lights.iter().enumerate() {
+            match light.bounds() {
+                None => infinite_lights.push(i),
+                // Lights that don't emit anything would never be chosen.
+                Some(bounds) if bounds.power > 0.0 => bounded.push((i, bounds)),
+                Some(_) => ()
+            }
+        }
+        let mut nodes = Vec::new();
+        let root = if bounded.is_empty() { None } else { Some(Self::build(&mut nodes, &mut bounded)) };
+
+        let mut parents = vec![None; nodes.len()];
+        let mut keys: HashMap<usize, Option<usize>> = infinite_lights.iter().map(|&i| (light_key(&lights[i]), None)).collect();
+        for (i, node) in nodes.iter().enumerate() {
+            match node {
+                LightBvhNode::Leaf { light, .. } => { keys.insert(light_key(&lights[*light]), Some(i)); },
+                LightBvhNode::Interior { children, .. } => children.iter().for_each(|&child| parents[child] = Some(i))
+            }
+        }
+        Self { lights, infinite_lights, nodes, parents, keys, root }
+    }
+
+    // Returns the index of the created node.
+    fn build(nodes: &mut Vec<LightBvhNode>, lights: &mut [(usize, LightBounds)]) -> usize {
+        if let [(light, bounds)] = lights {
+            nodes.push(LightBvhNode::Leaf { bounds: *bounds, light: *light });
+            return nodes.len() - 1;
+        }
+
+        let split = Self::best_split(lights).unwrap_or(lights.len() / 2);
+        let (left, right) = lights.split_at_mut(split);
+        let children = [Self::build(nodes, left), Self::build(nodes, right)];
+        let bounds = nodes[children[0]].bounds().combined(nodes[children[1]].bounds());
+        nodes.push(LightBvhNode::Interior { bounds, children });
+        nodes.len() - 1
+    }
+
+    // Tries splitting the lights into buckets along each axis and reorders them by the cheapest split.
+    // Returns the number of lights on the left or None if the lights can't be told apart by their positions.
+    fn best_split(lights: &mut [(usize, LightBounds)]) -> Option<usize> {
+        const BUCKET_COUNT: usize = 12;
+        let centroids = lights.iter().skip(1).fold((lights[0].1.centroid(), lights[0].1.centroid()), |(min, max), (_, bounds)| {
+            let c = bounds.centroid();
+            (Pt3::new(min.x.min(c.x), min.y.min(c.y), min.z.min(c.z)), Pt3::new(max.x.max(c.x), max.y.max(c.y), max.z.max(c.z)))
+        });
+        let bucket = |bounds: &LightBounds, axis: usize| {
+            let t = (bounds.centroid()[axis] - centroids.0[axis]) / (centroids.1[axis] - centroids.0[axis]);
+            usize::min((t * BUCKET_COUNT as f32) as usize, BUCKET_COUNT - 1)
+        };
+
+        let mut best: Option<(f32, usize, usize)> = None;
+        for axis in 0..3 {
+            if centroids.1[axis] <= centroids.0[axis] {
+                continue;
+            }
+            let mut buckets: [Option<LightBounds>; BUCKET_COUNT] = [None; BUCKET_COUNT];
+            for (_, bounds) in lights.iter() {
+                let b = &mut buckets[bucket(bounds, axis)];
+                *b = Some(b.map_or(*bounds, |existing| existing.combined(bounds)));
+            }
+            let combine = |buckets: &[Option<LightBounds>]| buckets.iter().flatten().copied().reduce(|a, b| a.combined(&b));
+            for split in 1..BUCKET_COUNT {
+                if let (Some(left), Some(right)) = (combine(&buckets[..split]), combine(&buckets[split..])) {
+                    let cost = left.cost() + right.cost();
+                    if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                        best = Some((cost, axis, split));
+                    }
+                }
+            }
+        }
+
+        let (_, axis, split) = best?;
+        lights.sort_by_key(|(_, bounds)| bucket(bounds, axis));
+        let count = lights.iter().filter(|(_, bounds)| bucket(bounds, axis) < split).count();
+        Some(count)
+    }
+}
+
+impl LightSampler for LightBvh {
+    fn sample(&self, point: Pt3) -> Option<(&Arc<dyn Light>, f32)> {
+        // Every infinite light gets the same chance as the whole tree.
+        let groups = self.infinite_lights.len() + usize::from(self.root.is_some());
+        if groups == 0 {
+            return None;
+        }
+        let group = rand::thread_rng().gen_range(0..groups);
+        let Some(root) = self.root.filter(|_| group == self.infinite_lights.len()) else {
+            return Some((&self.lights[self.infinite_lights[group]], 1.0 / groups as f32));
+        };
+
+        let mut probability = 1.0 / groups as f32;
+        let mut node = root;
+        loop {
+            match &self.nodes[node] {
+                LightBvhNode::Leaf { bounds, light } => {
+                    return if bounds.importance(point) > 0.0 { Some((&self.lights[*light], probability)) } else { None };
+                },
+                LightBvhNode::Interior { children, .. } => {
+                    let left = self.nodes[children[0]].bounds().importance(point);
+                    let right = self.nodes[children[1]].bounds().importance(point);
+                    if left + right <= 0.0 {
+                        return None;
+                    }
+                    let p_left = left / (left + right);
+                    if rand::thread_rng().gen::<f32>() < p_left {
+                        probability *= p_left;
+                        node = children[0];
+                    } else {
+                        probability *= 1.0 - p_left;
+                        node = children[1];
+                    }
+                }
+            }
+        }
+    }
+
+    fn probability(&self, point: Pt3, light: &Arc<dyn Light>) -> f32 {
+        let groups = self.infinite_lights.len() + usize::from(self.root.is_some());
+        let leaf = match self.keys.get(&light_key(light)) {
+            Some(Some(leaf)) => *leaf,
+            Some(None) => return 1.0 / groups as f32,
+            None => return 0.0
+        };
+        if self.nodes[leaf].bounds().importance(point) <= 0.0 {
+            return 0.0;
+        }
+
+        // Going up from the leaf multiplies the same probabilities of choosing each child as sampling does on the way down.
+        let mut probability = 1.0 / groups as f32;
+        let mut node = leaf;
+        while let Some(parent) = self.parents[node] {
+            let LightBvhNode::Interior { children, .. } = &self.nodes[parent] else { unreachable!("leaves have no children") };
+            let left = self.nodes[children[0]].bounds().importance(point);
+            let right = self.nodes[children[1]].bounds().importance(point);
+            if left + right <= 0.0 {
+                return 0.0;
+            }
+            let chosen = if children[0] == node { left } else { right };
+            probability *= chosen / (left + right);
+            node = parent;
+        }
+        probability
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lights::{PointLight, DirectionalLight};
+    use crate::vec3::{Vec3, Color};
+
+    #[test]
+    fn probability_matches_sampling() {
+        let mut lights: Vec<Arc<dyn Light>> = Vec::new();
+        for i in 0..8 {
+            let position = Pt3::new(i as f32 * 3.0, (i % 3) as f32, -(i as f32));
+            lights.push(Arc::new(PointLight::new(position, Color::all(1.0 + i as f32))));
+        }
+        lights.push(Arc::new(DirectionalLight::new(Vec3::new(0.0, -1.0, 0.0), Color::all(1.0))));
+        let bvh = LightBvh::new(lights.clone());
+        let point = Pt3::new(4.0, 2.0, 1.0);
+
+        const SAMPLES: usize = 200_000;
+        let mut counts: HashMap<usize, usize> = HashMap::new();
+        for _ in 0..SAMPLES {
+            let (light, probability) = bvh.sample(point).unwrap();
+            assert!((probability - bvh.probability(point, light)).abs() < 1e-5);
+            *counts.entry(light_key(light)).or_default() += 1;
+        }
+
+        let total: f32 = lights.iter().map(|light| bvh.probability(point, light)).sum();
+        assert!((total - 1.0).abs() < 1e-4);
+        for light in &lights {
+            let frequency = counts.get(&light_key(light)).copied().unwrap_or(0) as f32 / SAMPLES as f32;
+            assert!((frequency - bvh.probability(point, light)).abs() < 0.005);
+        }
+
+        // Lights that weren't given to the tree are never chosen.
+        let other: Arc<dyn Light> = Arc::new(PointLight::new(point, Color::all(1.0)));
+        assert_eq!(bvh.probability(point, &other), 0.0);
+    }
+}
@@ -0,0 +1,21 @@
+use std::sync::Arc;
+
+use crate::vec3::Pt3;
+
+use super::Light;
+
+// Chooses which light to sample from a point. Only one light is sampled per bounce, so with many lights
+// picking the ones that contribute the most is what decides how noisy the image is.
+pub trait LightSampler where Self: Send + Sync {
+    // Returns the chosen light and the probability of choosing it, or None if no light can reach the point.
+    fn sample(&self, point: Pt3) -> Option<(&Arc<dyn Light>, f32)>;
+
+    // Probability of `sample` choosing the light from the point. 0 for lights that aren't part of the sampler.
+    fn probability(&self, point: Pt3, light: &Arc<dyn Light>) -> f32;
+}
+
+// Identifies a light by its address so samplers can find it in a map. The vtable part of the pointer is dropped
+// because the same type can have more than one.
+pub fn light_key(light: &Arc<dyn Light>) -> usize {
+    Arc::as_ptr(light) as *const () as usize
+}
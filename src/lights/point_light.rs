@@ -0,0 +1,36 @@
+use std::f32::consts::PI;
+
+use crate::{vec3::{Vec3, Color, Pt3}, aabb::Aabb};
+
+use super::{Light, LightSample, LightBounds};
+
+// Infinitely small light shining equally in all directions.
+pub struct PointLight {
+    pub position: Pt3,
+    // Power per unit of solid angle.
+    pub intensity: Color
+}
+
+impl PointLight {
+    pub fn new(position: Pt3, intensity: Color) -> Self {
+        Self { position, intensity }
+    }
+}
+
+impl Light for PointLight {
+    fn sample(&self, point: Vec3) -> Option<LightSample> {
+        let to_light = self.position - point;
+        let distance_squared = to_light.length_squared();
+        if distance_squared == 0.0 {
+            return None;
+        }
+        let distance = distance_squared.sqrt();
+        // The light spreads over a sphere so it gets weaker with the square of the distance.
+        Some(LightSample { direction: to_light / distance, distance, radiance: self.intensity / distance_squared })
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        let intensity = (self.intensity.x + self.intensity.y + self.intensity.z) / 3.0;
+        Some(LightBounds::omnidirectional(Aabb::new(self.position, self.position), 4.0 * PI * intensity))
+    }
+}
@@ -0,0 +1,71 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::{vec3::Pt3, distribution::AliasTable};
+
+use super::{Light, LightSampler, light_key};
+
+// Lights are chosen proportionally to their power, so a dim light next to the point still gets few samples
+// but at least bright lights don't get drowned out by lots of weak ones.
+pub struct PowerLightSampler {
+    lights: Vec<Arc<dyn Light>>,
+    // Lights without bounds don't have a power so they are chosen uniformly.
+    infinite_lights: Vec<usize>,
+    finite_lights: Vec<usize>,
+    distribution: Option<AliasTable>,
+    // Index into the finite lights, or None for infinite lights.
+    keys: HashMap<usize, Option<usize>>
+}
+
+impl PowerLightSampler {
+    pub fn new(lights: Vec<Arc<dyn Light>>) -> Self {
+        let mut infinite_lights = Vec::new();
+        let mut finite_lights = Vec::new();
+        let mut powers = Vec::new();
+        let mut keys = HashMap::new();
+        for (i, light) in lights.iter().enumerate() {
+            match light.bounds() {
+                None => {
+                    infinite_lights.push(i);
+                    keys.insert(light_key(light), None);
+                },
+                Some(bounds) => {
+                    keys.insert(light_key(light), Some(finite_lights.len()));
+                    finite_lights.push(i);
+                    powers.push(bounds.power);
+                }
+            }
+        }
+        let distribution = if powers.is_empty() { None } else { Some(AliasTable::new(&powers)) };
+        Self { lights, infinite_lights, finite_lights, distribution, keys }
+    }
+}
+
+impl LightSampler for PowerLightSampler {
+    fn sample(&self, _point: Pt3) -> Option<(&Arc<dyn Light>, f32)> {
+        // Every infinite light gets the same chance as all of the finite lights together.
+        let groups = self.infinite_lights.len() + usize::from(self.distribution.is_some());
+        if groups == 0 {
+            return None;
+        }
+        let group = rand::thread_rng().gen_range(0..groups);
+        match &self.distribution {
+            Some(distribution) if group == self.infinite_lights.len() => {
+                let index = distribution.sample();
+                Some((&self.lights[self.finite_lights[index]], distribution.probability(index) / groups as f32))
+            },
+            _ => Some((&self.lights[self.infinite_lights[group]], 1.0 / groups as f32))
+        }
+    }
+
+    fn probability(&self, _point: Pt3, light: &Arc<dyn Light>) -> f32 {
+        let groups = self.infinite_lights.len() + usize::from(self.distribution.is_some());
+        match (self.keys.get(&light_key(light)), &self.distribution) {
+            (Some(Some(index)), Some(distribution)) => distribution.probability(*index) / groups as f32,
+            (Some(None), _) => 1.0 / groups as f32,
+            _ => 0.0
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use std::f32::consts::{PI, TAU};
+
+use rand::Rng;
+
+use crate::{vec3::{Vec3, Color, Pt3}, onb::Onb, aabb::Aabb};
+
+use super::{Light, LightSample, LightBounds};
+
+// Glowing sphere that creates soft shadows. Unlike a sphere with DiffuseLight it is invisible to rays
+// and only lights the scene.
+pub struct SphereLight {
+    pub center: Pt3,
+    pub radius: f32,
+    // Light leaving every point of the surface.
+    pub radiance: Color
+}
+
+impl SphereLight {
+    pub fn new(center: Pt3, radius: f32, radiance: Color) -> Self {
+        Self { center, radius, radiance }
+    }
+}
+
+impl Light for SphereLight {
+    fn sample(&self, point: Vec3) -> Option<LightSample> {
+        let to_center = self.center - point;
+        let distance_squared = to_center.length_squared();
+        let radius_squared = self.radius * self.radius;
+        let (r1, r2): (f32, f32) = (rand::thread_rng().gen(), rand::thread_rng().gen());
+
+        if distance_squared <= radius_squared {
+            // Inside the sphere light arrives from everywhere.
+            let direction = Vec3::random_unit();
+            let along = Vec3::dot(to_center, direction);
+            let distance = along + f32::sqrt(along * along - distance_squared + radius_squared);
+            return Some(LightSample { direction, distance, radiance: 4.0 * PI * self.radiance });
+        }
+
+        // Only directions inside the cone that the sphere covers when seen from the point can hit it.
+        // Sampling them uniformly is a lot better than sampling points on the sphere since half of it isn't visible.
+        let cos_max = f32::sqrt(f32::max(0.0, 1.0 - radius_squared / distance_squared));
+        let cos = 1.0 - r1 * (1.0 - cos_max);
+        let sin = f32::sqrt(f32::max(0.0, 1.0 - cos * cos));
+        let phi = TAU * r2;
+        let direction = Onb::from_w(to_center).local(Vec3::new(sin * phi.cos(), sin * phi.sin(), cos)).normalized();
+
+        // Distance to the closer intersection with the sphere.
+        let along = Vec3::dot(to_center, direction);
+        let distance = along - f32::sqrt(f32::max(0.0, along * along - distance_squared + radius_squared));
+        let solid_angle = TAU * (1.0 - cos_max);
+        Some(LightSample { direction, distance, radiance: solid_angle * self.radiance })
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        let radiance = (self.radiance.x + self.radiance.y + self.radiance.z) / 3.0;
+        let area = 4.0 * PI * self.radius * self.radius;
+        let bounds = Aabb::new(self.center - Vec3::all(self.radius), self.center + Vec3::all(self.radius));
+        Some(LightBounds::omnidirectional(bounds, PI * area * radiance))
+    }
+}
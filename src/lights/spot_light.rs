@@ -0,0 +1,84 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use crate::{vec3::{Vec3, Color, Pt3}, onb::Onb, aabb::Aabb};
+
+use super::{Light, LightSample, LightBounds, IesProfile};
+
+// Point light that only shines into a cone.
+pub struct SpotLight {
+    pub position: Pt3,
+    // The direction the cone points in.
+    pub direction: Vec3,
+    // Angle between the direction and the edge of the cone in radians.
+    pub cone_angle: f32,
+    // Fraction of the cone over which the light smoothly fades out towards the edge. 0 is a hard edge.
+    pub falloff: f32,
+    // Intensity in the direction of the cone.
+    pub intensity: Color,
+    // Measured light distribution of a real fixture. Replaces the cone when set.
+    pub profile: Option<Arc<IesProfile>>
+}
+
+impl SpotLight {
+    pub fn new(position: Pt3, direction: Vec3, cone_angle: f32, falloff: f32, intensity: Color) -> Self {
+        Self { position, direction: direction.normalized(), cone_angle, falloff: falloff.clamp(0.0, 1.0), intensity, profile: None }
+    }
+
+    pub fn with_profile(self, profile: Arc<IesProfile>) -> Self {
+        Self { profile: Some(profile), ..self }
+    }
+
+    // How much of the intensity goes in the direction, which points away from the light.
+    fn attenuation(&self, direction: Vec3) -> f32 {
+        if let Some(profile) = &self.profile {
+            // The profile's angles are measured from the direction of the light and around it.
+            let frame = Onb::from_w(self.direction);
+            let vertical = f32::acos(Vec3::dot(direction, frame.w).clamp(-1.0, 1.0));
+            let horizontal = f32::atan2(Vec3::dot(direction, frame.v), Vec3::dot(direction, frame.u));
+            return profile.value(vertical, horizontal);
+        }
+
+        let cos = Vec3::dot(direction, self.direction);
+        let cos_outer = self.cone_angle.cos();
+        let cos_inner = (self.cone_angle * (1.0 - self.falloff)).cos();
+        if cos >= cos_inner {
+            return 1.0;
+        }
+        if cos <= cos_outer {
+            return 0.0;
+        }
+        // Smoothstep so there is no visible edge where the falloff starts.
+        let t = (cos - cos_outer) / (cos_inner - cos_outer);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(&self, point: Vec3) -> Option<LightSample> {
+        let to_light = self.position - point;
+        let distance_squared = to_light.length_squared();
+        if distance_squared == 0.0 {
+            return None;
+        }
+        let distance = distance_squared.sqrt();
+        let direction = to_light / distance;
+        let attenuation = self.attenuation(-direction);
+        if attenuation <= 0.0 {
+            return None;
+        }
+        Some(LightSample { direction, distance, radiance: attenuation * self.intensity / distance_squared })
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        let bounds = Aabb::new(self.position, self.position);
+        // Using the power of a point light even though the cone is smaller. Otherwise narrow spots
+        // would hardly ever be chosen even for points right in front of them.
+        let power = 4.0 * PI * (self.intensity.x + self.intensity.y + self.intensity.z) / 3.0;
+        if self.profile.is_some() {
+            return Some(LightBounds::omnidirectional(bounds, power));
+        }
+        let inner = self.cone_angle * (1.0 - self.falloff);
+        Some(LightBounds::new(bounds, power, self.direction, inner, self.cone_angle - inner))
+    }
+}
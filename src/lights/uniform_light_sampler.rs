@@ -0,0 +1,35 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::vec3::Pt3;
+
+use super::{Light, LightSampler, light_key};
+
+// Every light is equally likely. Fine for a handful of similar lights.
+pub struct UniformLightSampler {
+    lights: Vec<Arc<dyn Light>>,
+    keys: HashSet<usize>
+}
+
+impl UniformLightSampler {
+    pub fn new(lights: Vec<Arc<dyn Light>>) -> Self {
+        let keys = lights.iter().map(light_key).collect();
+        Self { lights, keys }
+    }
+}
+
+impl LightSampler for UniformLightSampler {
+    fn sample(&self, _point: Pt3) -> Option<(&Arc<dyn Light>, f32)> {
+        if self.lights.is_empty() {
+            return None;
+        }
+        let index = rand::thread_rng().gen_range(0..self.lights.len());
+        Some((&self.lights[index], 1.0 / self.lights.len() as f32))
+    }
+
+    fn probability(&self, _point: Pt3, light: &Arc<dyn Light>) -> f32 {
+        if self.keys.contains(&light_key(light)) { 1.0 / self.lights.len() as f32 } else { 0.0 }
+    }
+}
@@ -2,29 +2,123 @@ mod vec3;
 mod vec2;
 mod ray;
 mod aabb;
-mod camera;
+mod cameras;
 mod materials;
+mod density_fields;
 mod hittable_objects;
 mod textures;
 mod perlin;
+mod worley;
+mod simplex;
+mod onb;
+mod distribution;
+mod environments;
+mod lights;
+mod height_fog;
 mod raytracer;
 
-use std::{sync::Arc, path::Path};
+use std::{sync::Arc, path::Path, error::Error, f32::consts::PI};
 
-use hittable_objects::{HittableList, XyRect, YzRect};
-use materials::Material;
+use hittable_objects::{HittableList, XyRect, YzRect, HeterogeneousMedium, ConstantMedium, AlphaMask};
+use materials::{Material, Isotropic, EmissiveVolume, CoatedMaterial, Microfacet, ThinFilm, FilmBase, Sheen, Subsurface, MixMaterial, BumpMap, NormalMap, OrenNayar, HenyeyGreenstein};
 use rand::Rng;
+use image::{DynamicImage, RgbaImage, Rgba};
 use raytracer::{Scene, run_raytracer};
-use textures::{CheckerTexture, NoiseTexture};
-
-use crate::{materials::{Lambertian, DiffuseLight, Dielectric, Metal}, hittable_objects::{Hittable, AaBox, BhvNode, XzRect, RotateY, Translate, Sphere}, vec3::{Color, Pt3, Vec3}, textures::{SolidColor, ImageTexture}};
-
+use cameras::{Projection, FisheyeMapping};
+use environments::{ConstantEnvironment, GradientEnvironment, EnvironmentMap, PhysicalSky};
+use lights::{Light, AreaLight, LightBvh, PowerLightSampler, UniformLightSampler, PointLight, SpotLight, DirectionalLight, SphereLight, IesProfile, IesLoadError};
+use textures::{CheckerTexture, NoiseTexture, Texture, MarbleTexture, WoodTexture, WorleyTexture, WorleyOutput, SimplexTexture, ColorRamp, GridTexture, UvCheckerTexture, TriplanarTexture, UvTransformTexture, UvTransform, PositionTransform, HsvAdjust, RampTexture, LerpTexture, AddTexture, MultiplyTexture, ScaleTexture, InvertTexture, ClampTexture, AlphaChannel, WrapMode, ImageTexture, ColorSpace};
+use density_fields::{VoxelVolume, VolumeLoadError, NoiseDensity};
+use height_fog::HeightFog;
+use worley::DistanceMetric;
+use vec2::Vec2;
+use aabb::Aabb;
+
+use crate::{materials::{Lambertian, DiffuseLight, Dielectric, Metal}, hittable_objects::{Hittable, AaBox, BhvNode, XzRect, RotateY, Translate, Sphere, FlipFace}, vec3::{Color, Pt3, Vec3}, textures::{SolidColor, TextureCache, TextureLoadError}};
+
+// Usage: ray_tracing [scene] [projection] [file]
+// The file is the HDRI of environment_map, the IES profile of stage, the voxel volume of fire and an optional decal of textures.
 fn main() {
     std::env::set_var("RUST_BACKTRACE", "1");
-    run_raytracer(Path::new("out.ppm"), cornell_box(), 1.0);
+    let args: Vec<String> = std::env::args().collect();
+    let name = args.get(1).map_or("cornell_box", String::as_str);
+
+    let textures = TextureCache::new();
+    let (mut scene, aspect_ratio) = match load_scene(name, args.get(3).map(Path::new), &textures) {
+        Ok(scene) => scene,
+        Err(error) => {
+            println!("couldn't load scene {}: {}", name, error);
+            std::process::exit(-1);
+        }
+    };
+    if let Some(projection) = args.get(2) {
+        scene.projection = match projection_from_name(projection, &scene) {
+            Some(projection) => projection,
+            None => {
+                println!("unknown projection {}", projection);
+                std::process::exit(-1);
+            }
+        };
+    }
+    run_raytracer(Path::new("out.ppm"), scene, aspect_ratio);
+}
+
+// Returns the scene and the aspect ratio of the image it's framed for.
+fn load_scene(name: &str, file: Option<&Path>, textures: &TextureCache) -> Result<(Scene, f32), Box<dyn Error>> {
+    let wide = 16.0 / 9.0;
+    Ok(match name {
+        "cornell_box" => (cornell_box(), 1.0),
+        "balls" => (balls_scene(textures)?, wide),
+        "final" => (test_scene(textures)?, 1.0),
+        "simple" => (daylight_scene(simple_scene(), Pt3::new(-2.0, 2.0, 1.0), Pt3::new(0.0, 0.0, -1.0)), wide),
+        "noise" => (daylight_scene(noise_scene(), Pt3::new(13.0, 2.0, 3.0), Pt3::new(0.0, 0.0, 0.0)), wide),
+        "earth" => (earth_scene(textures)?, wide),
+        "light" => (light_scene(), wide),
+        "signage" => (signage_scene(), wide),
+        "environment_map" => (environment_map_scene(file.ok_or("needs an HDR image")?)?, wide),
+        "sky" => (sky_scene(), wide),
+        "stage" => (stage_scene(file.ok_or("needs an IES profile")?)?, wide),
+        "fire" => (fire_scene(file.ok_or("needs a voxel volume")?)?, 1.0),
+        "materials" => (materials_scene(), wide),
+        "textures" => (textures_scene(textures, file)?, wide),
+        _ => return Err("unknown scene".into())
+    })
+}
+
+// Replaces the camera of a scene, for example to render a panorama of it.
+fn projection_from_name(name: &str, scene: &Scene) -> Option<Projection> {
+    match name {
+        "perspective" => Some(Projection::Perspective),
+        // Covers the same height at the look at point as the perspective camera.
+        "orthographic" => Some(Projection::Orthographic {
+            view_height: 2.0 * (scene.look_at - scene.look_from).length() * f32::tan(scene.vertical_fov / 2.0)
+        }),
+        "fisheye" => Some(Projection::Fisheye { fov: PI, mapping: FisheyeMapping::Equisolid }),
+        "fisheye_equidistant" => Some(Projection::Fisheye { fov: PI, mapping: FisheyeMapping::Equidistant }),
+        "equirectangular" => Some(Projection::Equirectangular),
+        "cube_map" => Some(Projection::CubeMap),
+        _ => None
+    }
+}
+
+// Outdoor scene lit only by the sky for the simple lists of objects.
+fn daylight_scene(world: HittableList, look_from: Pt3, look_at: Pt3) -> Scene {
+    Scene { 
+        objects: Arc::new(world),
+        lights: Arc::new(LightBvh::new(Vec::new())),
+        projection: Projection::Perspective,
+        look_from, 
+        look_at,
+        vertical_fov: 20.0f32.to_radians(), 
+        aperture: 0.0,
+        environment: Arc::new(GradientEnvironment::sky()),
+        camera_environment: None,
+        fog: None,
+        focus_distance: 10.0
+    }
 }
 
-fn balls_scene() -> Scene {
+fn balls_scene(textures: &TextureCache) -> Result<Scene, TextureLoadError> {
     let mut world = HittableList::new();
 
     let ground_material: Arc<dyn Material> = Arc::new(Lambertian::new(Arc::new(CheckerTexture::new(
@@ -67,22 +161,26 @@ fn balls_scene() -> Scene {
     let material = Arc::new(Dielectric::new(1.5));
     world.add(Arc::new(Sphere::new(Vec3::new(0.0, 1.0, 0.0), 1.0, material)));
 
-    let earth_texture = Arc::new(ImageTexture::from_file(Path::new("earthmap.jpg")));
+    let earth_texture = Arc::new(textures.load(Path::new("earthmap.jpg"))?);
     let material: Arc<dyn Material> = Arc::new(Lambertian::new(earth_texture));
     world.add(Arc::new(Sphere::new(Vec3::new(-4.0, 1.0, 0.0), 1.0, material)));
     
-    Scene { 
+    Ok(Scene { 
         objects: Arc::new(world),
+        lights: Arc::new(LightBvh::new(Vec::new())),
+        projection: Projection::Perspective,
         look_from: Vec3::new(13.0, 2.0, 3.0), 
         look_at: Vec3::new(0.0, 0.0, 0.0),
         vertical_fov: 20.0f32.to_radians(), 
         aperture: 0.1,
-        background_color: Vec3::new(0.7, 0.8, 1.0),
+        environment: Arc::new(GradientEnvironment::sky()),
+        camera_environment: None,
+        fog: None,
         focus_distance: 10.0
-    }
+    })
 }
 
-fn test_scene() -> Scene {
+fn test_scene(textures: &TextureCache) -> Result<Scene, TextureLoadError> {
     let mut boxes1: Vec<Arc<dyn Hittable>> = Vec::new();
     let ground = Arc::new(Lambertian::from_color(Color::new(0.48, 0.83, 0.53)));
 
@@ -112,7 +210,7 @@ fn test_scene() -> Scene {
     objects.push(Arc::new(Sphere::new(Pt3::new(260.0, 150.0, 45.0), 50.0, Arc::new(Dielectric::new(0.5)))));
     objects.push(Arc::new(Sphere::new(Pt3::new(0.0, 150.0, 145.0), 50.0, Arc::new(Metal::new(&Color::new(0.8, 0.8, 0.9), 1.0)))));
 
-    let emat = Arc::new(Lambertian::new(Arc::new(ImageTexture::from_file(Path::new("earthmap.jpg")))));
+    let emat = Arc::new(Lambertian::new(Arc::new(textures.load(Path::new("earthmap.jpg"))?)));
     objects.push(Arc::new(Sphere::new(Pt3::new(400.0,200.0,400.0), 100.0, emat)));
     // auto pertext = make_shared<noise_texture>(0.1);
     // objects.add(make_shared<sphere>(point3(220,280,300), 80, make_shared<lambertian>(pertext)));
@@ -131,16 +229,20 @@ fn test_scene() -> Scene {
             Vec3::new(-100.0, 270.0, 395.0))
     ));
 
-    Scene { 
+    Ok(Scene { 
         objects: Arc::new(BhvNode::new(&objects, 0, objects.len())),
+        lights: Arc::new(LightBvh::new(Vec::new())),
         // objects: Box::new(boxes1),
+        projection: Projection::Perspective,
         look_from: Vec3::new(478.0, 278.0, -600.0), 
         look_at: Vec3::new(278.0, 278.0, 0.0),
         vertical_fov: 40.0f32.to_radians(), 
         aperture: 0.1,
-        background_color: Vec3::all(0.8),
+        environment: Arc::new(ConstantEnvironment::new(Color::all(0.8))),
+        camera_environment: None,
+        fog: None,
         focus_distance: 10.0
-    }
+    })
 }
 
 fn light_scene() -> Scene {
@@ -151,16 +253,60 @@ fn light_scene() -> Scene {
     world.objects.push(Arc::new(Sphere::new(Pt3::new(0.0 , 2.0, 0.0), 2.0, texture)));
 
     let light = Arc::new(DiffuseLight::new(Arc::new(SolidColor::new(Color::all(4.0)))));
-    world.objects.push(Arc::new(XyRect::new(3.0, 5.0, 1.0, 3.0, -2.0, light)));
-    // world.objects.push(Arc::new(Sphere::new(Vec3::new(3.0, 1.0, -2.0), 1.0, light)));
+    let rect_light = AreaLight::new(Arc::new(XyRect::new(3.0, 5.0, 1.0, 3.0, -2.0, light.clone())));
+    let sphere_light = AreaLight::new(Arc::new(Sphere::new(Vec3::new(0.0, 7.0, 0.0), 1.0, light)));
+    world.objects.push(rect_light.clone());
+    world.objects.push(sphere_light.clone());
 
     Scene { 
         objects: Arc::new(world),
+        lights: Arc::new(PowerLightSampler::new(vec![rect_light, sphere_light])),
+        projection: Projection::Perspective,
         look_from: Vec3::new(26.0, 3.0, 6.0), 
         look_at: Vec3::new(0.0, 2.0, 0.0),
         vertical_fov: 20.0f32.to_radians(), 
         aperture: 0.1,
-        background_color: Vec3::all(0.0),
+        environment: Arc::new(ConstantEnvironment::new(Color::all(0.0))),
+        camera_environment: None,
+        fog: None,
+        focus_distance: 10.0
+    }
+}
+
+// A sign made of a grid of small colored bulbs. With hundreds of lights the light BVH picks the ones near each point.
+fn signage_scene() -> Scene {
+    let mut objects: Vec<Arc<dyn Hittable>> = Vec::new();
+    let mut lights: Vec<Arc<dyn Light>> = Vec::new();
+
+    let ground = Arc::new(Lambertian::from_color(Color::all(0.5)));
+    objects.push(Arc::new(XzRect::new(-50.0, 50.0, -50.0, 50.0, 0.0, ground)));
+    let wall = Arc::new(Lambertian::from_color(Color::all(0.2)));
+    objects.push(Arc::new(XyRect::new(-12.0, 12.0, 0.0, 10.0, 0.5, wall)));
+
+    const COLUMNS: i32 = 32;
+    const ROWS: i32 = 12;
+    for i in 0..COLUMNS {
+        for j in 0..ROWS {
+            let color = Color::new_random_in_range(0.2, 1.0);
+            let bulb = Arc::new(DiffuseLight::new(Arc::new(SolidColor::new(8.0 * color))));
+            let center = Pt3::new(-10.0 + 20.0 * i as f32 / (COLUMNS - 1) as f32, 1.5 + 6.0 * j as f32 / (ROWS - 1) as f32, 0.0);
+            let light = AreaLight::new(Arc::new(Sphere::new(center, 0.15, bulb)));
+            objects.push(light.clone());
+            lights.push(light);
+        }
+    }
+
+    Scene { 
+        objects: Arc::new(BhvNode::new(&objects, 0, objects.len())),
+        lights: Arc::new(LightBvh::new(lights)),
+        projection: Projection::Perspective,
+        look_from: Vec3::new(6.0, 3.0, -25.0), 
+        look_at: Vec3::new(0.0, 4.5, 0.0),
+        vertical_fov: 40.0f32.to_radians(), 
+        aperture: 0.0,
+        environment: Arc::new(ConstantEnvironment::new(Color::all(0.0))),
+        camera_environment: None,
+        fog: None,
         focus_distance: 10.0
     }
 }
@@ -183,40 +329,311 @@ fn simple_scene() -> HittableList {
 fn noise_scene() -> HittableList {
     let mut world = HittableList::new();
 
-    let material: Arc<dyn Material> = Arc::new(Lambertian::new(Arc::new(NoiseTexture::new())));
+    let material: Arc<dyn Material> = Arc::new(Lambertian::new(Arc::new(NoiseTexture::new(4.0))));
     world.objects.push(Arc::new(Sphere::new(Vec3::new(0.0,-1000.0,0.0), 1000.0, material.clone())));
     world.objects.push(Arc::new(Sphere::new(Vec3::new(0.0, 1.0, 0.0), 1.0, material)));
 
     world
 }
 
-fn earth_scene() -> Scene {
-    let texture =  ImageTexture::from_file(Path::new("earthmap.jpg"));
+fn earth_scene(textures: &TextureCache) -> Result<Scene, TextureLoadError> {
+    let texture = textures.load(Path::new("earthmap.jpg"))?;
     let material = Arc::new(Lambertian::new(Arc::new(texture)));
     let sphere = Sphere::new(Vec3::new(0.0, 0.0, 0.0), 2.0, material);
 
-    Scene { 
+    Ok(Scene { 
         objects: Arc::new(sphere),
+        lights: Arc::new(LightBvh::new(Vec::new())),
+        projection: Projection::Perspective,
         look_from: Vec3::new(13.0, 2.0, 3.0), 
         look_at: Vec3::new(0.0, 0.0, 0.0),
         vertical_fov: 20.0f32.to_radians(), 
         aperture: 0.1,
-        background_color: Vec3::new(0.7, 0.8, 1.0),
+        environment: Arc::new(GradientEnvironment::sky()),
+        camera_environment: None,
+        fog: None,
+        focus_distance: 10.0
+    })
+}
+
+// A few spheres lit only by an HDRI. The bright parts of the map are sampled directly so small light sources in it
+// like the sun or windows don't make the image noisy.
+fn environment_map_scene(path: &Path) -> Result<Scene, TextureLoadError> {
+    let mut world = HittableList::new();
+
+    let ground = Arc::new(Lambertian::from_color(Color::all(0.5)));
+    world.add(Arc::new(XzRect::new(-20.0, 20.0, -20.0, 20.0, 0.0, ground)));
+    world.add(Arc::new(Sphere::new(Pt3::new(-2.2, 1.0, 0.0), 1.0, Arc::new(Lambertian::from_color(Color::new(0.7, 0.3, 0.2))))));
+    world.add(Arc::new(Sphere::new(Pt3::new(0.0, 1.0, 0.0), 1.0, Arc::new(Metal::new(&Color::all(0.9), 0.05)))));
+    world.add(Arc::new(Sphere::new(Pt3::new(2.2, 1.0, 0.0), 1.0, Arc::new(Dielectric::new(1.5)))));
+
+    Ok(Scene { 
+        objects: Arc::new(world),
+        lights: Arc::new(LightBvh::new(Vec::new())),
+        projection: Projection::Perspective,
+        look_from: Vec3::new(0.0, 2.0, -9.0), 
+        look_at: Vec3::new(0.0, 1.0, 0.0),
+        vertical_fov: 35.0f32.to_radians(), 
+        aperture: 0.0,
+        environment: Arc::new(EnvironmentMap::from_file(path, 0.0, 1.0)?),
+        camera_environment: None,
+        fog: None,
+        focus_distance: 10.0
+    })
+}
+
+// Late afternoon sun over a few objects. The sun is tiny compared to the sky so it's sampled directly to get sharp
+// shadows without noise.
+fn sky_scene() -> Scene {
+    let mut world = HittableList::new();
+
+    let ground = Arc::new(Lambertian::from_color(Color::all(0.4)));
+    world.add(Arc::new(XzRect::new(-50.0, 50.0, -50.0, 50.0, 0.0, ground.clone())));
+    let block = Arc::new(AaBox::new(Pt3::new(-1.0, 0.0, -1.0), Pt3::new(1.0, 3.0, 1.0), ground));
+    world.add(Arc::new(Translate::new(Arc::new(RotateY::new(block, 30.0f32.to_radians())), Vec3::new(-2.5, 0.0, 1.0))));
+    world.add(Arc::new(Sphere::new(Pt3::new(1.5, 1.0, -0.5), 1.0, Arc::new(Lambertian::from_color(Color::new(0.2, 0.4, 0.7))))));
+    world.add(Arc::new(Sphere::new(Pt3::new(4.0, 0.7, 1.5), 0.7, Arc::new(Metal::new(&Color::all(0.8), 0.1)))));
+
+    Scene { 
+        objects: Arc::new(world),
+        lights: Arc::new(LightBvh::new(Vec::new())),
+        projection: Projection::Perspective,
+        look_from: Vec3::new(2.0, 2.5, -12.0), 
+        look_at: Vec3::new(0.0, 1.2, 0.0),
+        vertical_fov: 40.0f32.to_radians(), 
+        aperture: 0.0,
+        environment: Arc::new(PhysicalSky::new(20.0f32.to_radians(), 120.0f32.to_radians(), 3.0, Color::all(0.4))),
+        camera_environment: None,
+        fog: None,
         focus_distance: 10.0
     }
 }
 
+// A small stage lit only by lights that aren't objects. The spot on the left uses a measured IES profile
+// and the one on the right a plain cone.
+fn stage_scene(profile: &Path) -> Result<Scene, IesLoadError> {
+    let mut world = HittableList::new();
+
+    let white = Arc::new(Lambertian::from_color(Color::all(0.7)));
+    world.add(Arc::new(XzRect::new(-10.0, 10.0, -10.0, 10.0, 0.0, white.clone())));
+    world.add(Arc::new(XyRect::new(-10.0, 10.0, 0.0, 10.0, 4.0, white.clone())));
+    world.add(Arc::new(Sphere::new(Pt3::new(-2.0, 1.0, 1.0), 1.0, white)));
+    world.add(Arc::new(Sphere::new(Pt3::new(2.0, 1.0, 1.0), 1.0, Arc::new(Metal::new(&Color::new(0.9, 0.8, 0.6), 0.2)))));
+
+    let profile = Arc::new(IesProfile::from_file(profile)?);
+    let down = Vec3::new(0.0, -1.0, 0.0);
+    let lights: Vec<Arc<dyn Light>> = vec![
+        Arc::new(SpotLight::new(Pt3::new(-2.0, 6.0, 1.0), down, 0.5, 0.0, Color::all(60.0)).with_profile(profile)),
+        Arc::new(SpotLight::new(Pt3::new(2.0, 6.0, 1.0), down, 25.0f32.to_radians(), 0.3, Color::new(60.0, 50.0, 40.0))),
+        Arc::new(PointLight::new(Pt3::new(0.0, 3.0, -3.0), Color::all(4.0))),
+        Arc::new(SphereLight::new(Pt3::new(0.0, 8.0, 2.0), 0.5, Color::new(2.0, 3.0, 6.0))),
+        // Dim moonlight coming in from the side.
+        Arc::new(DirectionalLight::new(Vec3::new(1.0, -1.0, 0.5), Color::new(0.05, 0.05, 0.08)))
+    ];
+
+    Ok(Scene { 
+        objects: Arc::new(world),
+        lights: Arc::new(LightBvh::new(lights)),
+        projection: Projection::Perspective,
+        look_from: Vec3::new(0.0, 3.0, -10.0), 
+        look_at: Vec3::new(0.0, 1.5, 1.0),
+        vertical_fov: 40.0f32.to_radians(), 
+        aperture: 0.0,
+        environment: Arc::new(ConstantEnvironment::new(Color::all(0.0))),
+        camera_environment: None,
+        fog: None,
+        focus_distance: 10.0
+    })
+}
+
+// A fire simulation exported as a voxel volume burning at night in a foggy valley. The glow comes from the temperature
+// of the voxels, or from a plain orange when the file only has the emission.
+fn fire_scene(path: &Path) -> Result<Scene, VolumeLoadError> {
+    let mut world = HittableList::new();
+
+    let ground = Arc::new(Lambertian::from_color(Color::all(0.3)));
+    world.add(Arc::new(XzRect::new(-30.0, 30.0, -30.0, 30.0, 0.0, ground)));
+
+    let volume = VoxelVolume::from_file(path, Aabb::new(Pt3::new(-1.0, 0.0, -1.0), Pt3::new(1.0, 3.0, 1.0)))?;
+    let smoke: Arc<dyn Material> = Arc::new(Isotropic::new(Arc::new(SolidColor::new(Color::all(0.4)))));
+    let phase_function: Arc<dyn Material> = match (volume.emission, volume.temperature) {
+        (Some(emission), Some(temperature)) => Arc::new(EmissiveVolume::with_temperature(smoke, emission, temperature, 5.0)),
+        (Some(emission), None) => Arc::new(EmissiveVolume::new(smoke, emission, Color::new(1.0, 0.45, 0.1), 5.0)),
+        (None, _) => smoke
+    };
+    world.add(Arc::new(HeterogeneousMedium::from_voxel_grid(volume.density, phase_function)));
+
+    Ok(Scene { 
+        objects: Arc::new(world),
+        lights: Arc::new(LightBvh::new(Vec::new())),
+        projection: Projection::Perspective,
+        look_from: Vec3::new(0.0, 2.0, -10.0), 
+        look_at: Vec3::new(0.0, 1.5, 0.0),
+        vertical_fov: 35.0f32.to_radians(), 
+        aperture: 0.0,
+        environment: Arc::new(ConstantEnvironment::new(Color::new(0.01, 0.01, 0.03))),
+        camera_environment: None,
+        fog: Some(HeightFog::new(0.05, Color::all(0.9), 0.0, 0.4)),
+        focus_distance: 10.0
+    })
+}
+
+// Two rows of spheres each showing one material in daylight, with a smoke ball and a cloud.
+fn materials_scene() -> Scene {
+    let mut world = HittableList::new();
+
+    let ground = Arc::new(Lambertian::from_color(Color::all(0.5)));
+    world.add(Arc::new(XzRect::new(-50.0, 50.0, -50.0, 50.0, 0.0, ground)));
+
+    let red = Arc::new(Lambertian::from_color(Color::new(0.7, 0.1, 0.1)));
+    let gray = Arc::new(Lambertian::from_color(Color::all(0.6)));
+    let gold = Arc::new(Metal::new(&Color::new(1.0, 0.78, 0.34), 0.1));
+    let steel = Arc::new(Metal::new(&Color::all(0.8), 0.0));
+    let hammered = Arc::new(SimplexTexture::new(6.0, ColorRamp::new(vec![(0.0, Color::new(0.35, 0.35, 1.0)), (1.0, Color::new(0.65, 0.65, 1.0))])));
+    let front: Vec<Arc<dyn Material>> = vec![
+        // Glossy paint.
+        Arc::new(CoatedMaterial::new(red, 1.5)),
+        // Brushed copper.
+        Arc::new(Microfacet::from_color(Color::new(0.95, 0.64, 0.54), 0.05, 0.4)),
+        // Anodised metal.
+        Arc::new(ThinFilm::from_thickness(steel.clone(), FilmBase::Conductor, 300.0, 1.6, 10.0)),
+        // Velvet.
+        Arc::new(Sheen::from_colors(Color::new(0.05, 0.05, 0.3), Color::new(0.6, 0.6, 1.0), 0.3)),
+        // Wax.
+        Arc::new(Subsurface::new(Color::new(0.99, 0.95, 0.9), Color::new(0.4, 0.2, 0.1), 0.0, 1.5))
+    ];
+    let back: Vec<Arc<dyn Material>> = vec![
+        // Gold flaking off of gray paint.
+        Arc::new(MixMaterial::new(gray.clone(), gold, Arc::new(ClampTexture::new(Arc::new(NoiseTexture::new(3.0)), 0.45, 0.55)))),
+        Arc::new(BumpMap::new(gray.clone(), Arc::new(NoiseTexture::new(4.0)), 0.5)),
+        Arc::new(NormalMap::new(steel, hammered, 1.0)),
+        // Clay.
+        Arc::new(OrenNayar::from_color(Color::new(0.7, 0.45, 0.3), 0.6))
+    ];
+    for (i, material) in front.into_iter().enumerate() {
+        world.add(Arc::new(Sphere::new(Pt3::new(-6.0 + 3.0 * i as f32, 1.0, 0.0), 1.0, material)));
+    }
+    for (i, material) in back.into_iter().enumerate() {
+        world.add(Arc::new(Sphere::new(Pt3::new(-4.5 + 3.0 * i as f32, 1.0, 3.0), 1.0, material)));
+    }
+
+    // Fog that mostly scatters light forward so it glows when the sun is behind it.
+    let fog_boundary = Arc::new(Sphere::new(Pt3::new(7.5, 1.0, 3.0), 1.0, gray.clone()));
+    let fog = Arc::new(HenyeyGreenstein::new(Arc::new(SolidColor::new(Color::all(0.9))), 0.6));
+    world.add(Arc::new(ConstantMedium::new(fog_boundary, fog, 2.0)));
+
+    let cloud_boundary = Arc::new(AaBox::new(Pt3::new(-3.0, 4.0, 4.0), Pt3::new(3.0, 6.0, 7.0), gray));
+    let cloud = Arc::new(Isotropic::new(Arc::new(SolidColor::new(Color::all(0.9)))));
+    world.add(Arc::new(HeterogeneousMedium::new(cloud_boundary, cloud, Arc::new(NoiseDensity::new(4.0, 1.5)))));
+
+    Scene { 
+        objects: Arc::new(world),
+        lights: Arc::new(LightBvh::new(Vec::new())),
+        projection: Projection::Perspective,
+        look_from: Vec3::new(0.0, 5.0, -16.0), 
+        look_at: Vec3::new(0.0, 1.5, 1.5),
+        vertical_fov: 35.0f32.to_radians(), 
+        aperture: 0.0,
+        environment: Arc::new(GradientEnvironment::sky()),
+        camera_environment: None,
+        fog: None,
+        focus_distance: 10.0
+    }
+}
+
+// Procedural and image textures on spheres and a box, in front of a fence cut out with a mask. The decal
+// is hung on the fence using the alpha channel of its image. Without a file it's a generated round badge.
+fn textures_scene(textures: &TextureCache, decal: Option<&Path>) -> Result<Scene, TextureLoadError> {
+    let mut world = HittableList::new();
+
+    let solid = |color: Color| -> Arc<dyn Texture> { Arc::new(SolidColor::new(color)) };
+    let grid = GridTexture::new(solid(Color::all(0.2)), solid(Color::all(0.7)), Vec2::new(40.0, 40.0), 0.05);
+    world.add(Arc::new(XzRect::new(-20.0, 20.0, -20.0, 20.0, 0.0, Arc::new(Lambertian::new(Arc::new(grid))))));
+
+    // Both textures share the image decoded by the cache.
+    let earth = textures.load(Path::new("earthmap.jpg"))?;
+    let mirrored_earth = textures.load(Path::new("earthmap.jpg"))?.with_wrap_mode(WrapMode::Mirror).with_transform(UvTransform { scale: Vec2::new(2.0, 2.0), ..UvTransform::identity() });
+    let tilted_earth = UvTransformTexture::new(
+        Arc::new(earth.with_wrap_mode(WrapMode::Repeat)),
+        UvTransform { scale: Vec2::new(3.0, 3.0), offset: Vec2::all(0.0), rotation: PI / 4.0 }
+    );
+    // Rings going along the x axis instead of around the y axis.
+    let wood: Arc<dyn Texture> = Arc::new(PositionTransform::new(
+        Arc::new(WoodTexture::new(3.0, Color::new(0.75, 0.55, 0.35), Color::new(0.45, 0.25, 0.1))),
+        Vec3::all(1.0), Vec3::new(0.0, 0.0, 1.0), PI / 2.0, Vec3::all(0.0)
+    ));
+    let stones = ColorRamp::new(vec![(0.0, Color::all(0.1)), (0.1, Color::new(0.5, 0.45, 0.4)), (1.0, Color::new(0.7, 0.65, 0.6))]);
+    let heat = ColorRamp::new(vec![(0.0, Color::all(0.0)), (0.5, Color::new(0.8, 0.2, 0.0)), (1.0, Color::new(1.0, 0.9, 0.3))]);
+    let front: Vec<Arc<dyn Texture>> = vec![
+        Arc::new(MarbleTexture::new(4.0, Color::all(0.9), Color::all(0.2))),
+        wood.clone(),
+        Arc::new(WorleyTexture::new(4.0, WorleyOutput::F2MinusF1, DistanceMetric::Euclidean, stones)),
+        Arc::new(SimplexTexture::new_4d(3.0, 0.5, ColorRamp::grayscale())),
+        Arc::new(HsvAdjust::new(Arc::new(mirrored_earth), 0.5, 1.2, 1.0))
+    ];
+    let noise: Arc<dyn Texture> = Arc::new(NoiseTexture::new(3.0));
+    let rust = AddTexture::new(Arc::new(ScaleTexture::new(noise.clone(), Color::new(0.4, 0.15, 0.05))), solid(Color::new(0.2, 0.08, 0.02)));
+    let painted_wood = MultiplyTexture::new(wood, solid(Color::new(0.6, 0.8, 1.0)));
+    let back: Vec<Arc<dyn Texture>> = vec![
+        Arc::new(RampTexture::new(Arc::new(ClampTexture::new(Arc::new(InvertTexture::new(noise)), 0.2, 0.8)), heat)),
+        Arc::new(LerpTexture::new(Arc::new(rust), Arc::new(painted_wood), Arc::new(UvCheckerTexture::new(solid(Color::all(0.0)), solid(Color::all(1.0)), Vec2::new(8.0, 4.0))))),
+        Arc::new(WorleyTexture::new(6.0, WorleyOutput::F1, DistanceMetric::Manhattan, ColorRamp::grayscale())),
+        Arc::new(WorleyTexture::new(6.0, WorleyOutput::F2, DistanceMetric::Chebyshev, ColorRamp::grayscale())),
+        Arc::new(tilted_earth)
+    ];
+    for (i, texture) in front.into_iter().enumerate() {
+        world.add(Arc::new(Sphere::new(Pt3::new(-6.0 + 3.0 * i as f32, 1.0, 0.0), 1.0, Arc::new(Lambertian::new(texture)))));
+    }
+    for (i, texture) in back.into_iter().enumerate() {
+        world.add(Arc::new(Sphere::new(Pt3::new(-6.0 + 3.0 * i as f32, 1.0, 3.0), 1.0, Arc::new(Lambertian::new(texture)))));
+    }
+    // The box has no texture coordinates so the checkers are projected onto it.
+    let checkers = UvCheckerTexture::new(solid(Color::new(0.7, 0.1, 0.1)), solid(Color::all(0.9)), Vec2::new(2.0, 2.0));
+    let block = Arc::new(AaBox::new(Pt3::all(-1.0), Pt3::all(1.0), Arc::new(Lambertian::new(Arc::new(TriplanarTexture::new(Arc::new(checkers), 1.0, 4.0))))));
+    world.add(Arc::new(Translate::new(Arc::new(RotateY::new(block, 30.0f32.to_radians())), Vec3::new(0.0, 1.0, -3.0))));
+
+    let fence_bars = GridTexture::new(solid(Color::all(1.0)), solid(Color::all(0.0)), Vec2::new(20.0, 4.0), 0.15);
+    let fence = Arc::new(XyRect::new(-10.0, 10.0, 0.0, 3.0, 6.0, Arc::new(Metal::new(&Color::all(0.6), 0.3))));
+    world.add(Arc::new(AlphaMask::new(fence, Arc::new(fence_bars))));
+    // Decals are only used once so they don't go through the cache.
+    let decal = Arc::new(match decal {
+        Some(path) => ImageTexture::from_file(path)?,
+        None => ImageTexture::from_image(DynamicImage::ImageRgba8(RgbaImage::from_fn(64, 64, |x, y| {
+            let inside = (x as f32 - 31.5).hypot(y as f32 - 31.5) < 30.0;
+            Rgba([230, 180, 40, if inside { 255 } else { 0 }])
+        })), ColorSpace::Srgb)
+    });
+    let sign = Arc::new(XyRect::new(-0.75, 0.75, 3.2, 4.7, 5.9, Arc::new(Lambertian::new(decal.clone()))));
+    world.add(Arc::new(AlphaMask::new(sign, Arc::new(AlphaChannel::new(decal)))));
+
+    Ok(Scene { 
+        objects: Arc::new(world),
+        lights: Arc::new(LightBvh::new(Vec::new())),
+        projection: Projection::Perspective,
+        look_from: Vec3::new(0.0, 4.0, -14.0), 
+        look_at: Vec3::new(0.0, 1.5, 2.0),
+        vertical_fov: 35.0f32.to_radians(), 
+        aperture: 0.0,
+        environment: Arc::new(GradientEnvironment::sky()),
+        camera_environment: None,
+        fog: None,
+        focus_distance: 10.0
+    })
+}
+
 fn cornell_box() -> Scene {
     let mut objects = HittableList::new();
 
     let red   = Arc::new(Lambertian::from_color(Color::new(0.65, 0.05, 0.05)));
     let white = Arc::new(Lambertian::from_color(Color::all(0.73)));
     let green = Arc::new(Lambertian::from_color(Color::new(0.12, 0.45, 0.15)));
-    let light = Arc::new(DiffuseLight::new(Arc::new(SolidColor::new(Color::all(15.0)))));
+    let light = Arc::new(DiffuseLight::one_sided(Arc::new(SolidColor::new(Color::all(1.0))), 15.0));
 
     objects.add(Arc::new(YzRect::new(0.0, 555.0, 0.0, 555.0, 555.0, green)));
     objects.add(Arc::new(YzRect::new(0.0, 555.0, 0.0, 555.0, 0.0, red)));
-    objects.add(Arc::new(XzRect::new(213.0, 343.0, 227.0, 332.0, 554.0, light)));
+    // The normal of the rect points up so the front face has to be flipped to face the inside of the box.
+    let light = AreaLight::new(Arc::new(FlipFace::new(Arc::new(XzRect::new(213.0, 343.0, 227.0, 332.0, 554.0, light)))));
+    objects.add(light.clone());
     objects.add(Arc::new(XzRect::new(0.0, 555.0, 0.0, 555.0, 0.0, white.clone())));
     objects.add(Arc::new(XzRect::new(0.0, 555.0, 0.0, 555.0, 555.0, white.clone())));
     objects.add(Arc::new(XyRect::new(0.0, 555.0, 0.0, 555.0, 555.0, white.clone())));
@@ -234,11 +651,15 @@ fn cornell_box() -> Scene {
     
     Scene { 
         objects: Arc::new(objects),
+        lights: Arc::new(UniformLightSampler::new(vec![light])),
+        projection: Projection::Perspective,
         look_from: Vec3::new(278.0, 278.0, -800.0), 
         look_at: Vec3::new(278.0, 278.0, 0.0),
         vertical_fov: 40.0f32.to_radians(), 
         aperture: 0.0,
-        background_color: Vec3::all(0.0),
+        environment: Arc::new(ConstantEnvironment::new(Color::all(0.0))),
+        camera_environment: None,
+        fog: None,
         focus_distance: 10.0
     }
 }
\ No newline at end of file
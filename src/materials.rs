@@ -14,4 +14,37 @@ mod diffuse_light;
 pub use diffuse_light::*;
 
 mod isotropic;
-pub use isotropic::*;
\ No newline at end of file
+pub use isotropic::*;
+
+mod henyey_greenstein;
+pub use henyey_greenstein::*;
+
+mod mix_material;
+pub use mix_material::*;
+
+mod coated_material;
+pub use coated_material::*;
+
+mod normal_map;
+pub use normal_map::*;
+
+mod bump_map;
+pub use bump_map::*;
+
+mod subsurface;
+pub use subsurface::*;
+
+mod thin_film;
+pub use thin_film::*;
+
+mod oren_nayar;
+pub use oren_nayar::*;
+
+mod sheen;
+pub use sheen::*;
+
+mod microfacet;
+pub use microfacet::*;
+
+mod emissive_volume;
+pub use emissive_volume::*;
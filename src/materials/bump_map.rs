@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use crate::{textures::Texture, ray::Ray, hittable_objects::HitRecord, vec3::{Color, Vec3}, vec2::Vec2};
+
+use super::{Material, ScatterRecord};
+
+// Changes the normal the base material sees as if the surface was displaced along the normal by the height texture.
+pub struct BumpMap {
+    pub base: Arc<dyn Material>,
+    pub height: Arc<dyn Texture>,
+    pub strength: f32
+}
+
+impl BumpMap {
+    pub fn new(base: Arc<dyn Material>, height: Arc<dyn Texture>, strength: f32) -> Self {
+        Self { base, height, strength }
+    }
+
+    fn shading_normal(&self, hit_record: &HitRecord) -> Vec3 {
+        // Distance the point is moved by when calculating the finite differences.
+        // The step is the same in world space so textures that use the hit point and the ones that use the texture coordinates both work.
+        const DELTA: f32 = 0.001;
+        let HitRecord { point, tangent, bitangent, texture_coord: uv, .. } = *hit_record;
+        // The bumps are on the outside of the surface. Seen from the back their normal is just reversed.
+        let normal = if hit_record.is_front_face { hit_record.normal } else { -hit_record.normal };
+
+        let height_near = |moved_uv: Vec2, moved_point: Vec3| {
+            let mut moved = hit_record.clone();
+            moved.texture_coord = moved_uv;
+            moved.point = moved_point;
+            self.height.value_at(&moved)
+        };
+
+        let height = self.height.value_at(hit_record);
+        let du = DELTA / tangent.length();
+        let dv = DELTA / bitangent.length();
+        let height_u = height_near(Vec2::new(uv.x + du, uv.y), point + du * tangent);
+        let height_v = height_near(Vec2::new(uv.x, uv.y + dv), point + dv * bitangent);
+
+        // The displaced point is p + strength * height * normal. Ignoring the change of the normal itself
+        // its derivatives are the surface derivatives moved along the normal by the change in height.
+        let displaced_tangent = tangent + (self.strength * (height_u - height) / du) * normal;
+        let displaced_bitangent = bitangent + (self.strength * (height_v - height) / dv) * normal;
+        let shading_normal = Vec3::cross(displaced_tangent, displaced_bitangent).normalized();
+        // The tangents may form a left handed basis so the result has to be flipped to the outside first.
+        let outward = if Vec3::dot(shading_normal, normal) < 0.0 { -shading_normal } else { shading_normal };
+        if hit_record.is_front_face { outward } else { -outward }
+    }
+}
+
+impl Material for BumpMap {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let mut shading_record = hit_record.clone();
+        shading_record.normal = self.shading_normal(hit_record);
+        self.base.scatter(ray, &shading_record)
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let mut shading_record = hit_record.clone();
+        shading_record.normal = self.shading_normal(hit_record);
+        self.base.evaluate(ray, &shading_record, direction)
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        let mut shading_record = hit_record.clone();
+        shading_record.normal = self.shading_normal(hit_record);
+        self.base.pdf(ray, &shading_record, direction)
+    }
+
+    fn color_emmited(&self, hit_record: &HitRecord) -> Color {
+        self.base.color_emmited(hit_record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::Lambertian, vec3::Pt3};
+
+    // Height growing along x, so the surface is a ramp.
+    struct Ramp;
+
+    impl Texture for Ramp {
+        fn color(&self, _: Vec2, hit_point: Pt3) -> Color {
+            Color::all(hit_point.x)
+        }
+    }
+
+    fn hit(material: Arc<dyn Material>, from_front: bool) -> HitRecord {
+        let direction = Vec3::new(0.0, 0.0, if from_front { -1.0 } else { 1.0 });
+        let ray = Ray::new(-direction, direction);
+        HitRecord::new(Vec3::all(0.0), &ray, Vec3::new(0.0, 0.0, 1.0), 1.0, Vec2::all(0.0), material)
+            .with_tangents(Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0))
+    }
+
+    #[test]
+    fn ramp_tilts_the_normal() {
+        let bump = Arc::new(BumpMap::new(Arc::new(Lambertian::from_color(Color::all(0.5))), Arc::new(Ramp), 0.5));
+        // The displaced surface is z = 0.5 x.
+        let expected = Vec3::new(-0.5, 0.0, 1.0).normalized();
+        let front = bump.shading_normal(&hit(bump.clone(), true));
+        assert!((front - expected).length() < 1e-3);
+
+        // From behind it's the same ramp so the normal is reversed, not mirrored.
+        let back = bump.shading_normal(&hit(bump.clone(), false));
+        assert!((back + expected).length() < 1e-3);
+    }
+}
@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::{ray::Ray, hittable_objects::HitRecord, vec3::{Color, Vec3}};
+
+use super::{Material, ScatterRecord, Dielectric};
+
+// A thin clear dielectric layer, like varnish or a car's clear coat, on top of any other material.
+pub struct CoatedMaterial {
+    pub base: Arc<dyn Material>,
+    pub index_of_refraction: f32
+}
+
+impl CoatedMaterial {
+    pub fn new(base: Arc<dyn Material>, index_of_refraction: f32) -> Self {
+        Self { base, index_of_refraction }
+    }
+}
+
+impl Material for CoatedMaterial {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        // The coat is infinitely thin so rays inside the object only see the base.
+        if !hit_record.is_front_face {
+            return self.base.scatter(ray, hit_record);
+        }
+
+        let refraction_ratio = 1.0 / self.index_of_refraction;
+        let direction = ray.direction.normalized();
+        let cos = f32::min(Vec3::dot(-direction, hit_record.normal), 1.0);
+
+        // The fraction of light reflected by the coat is given by the fresnel equations and the rest is transmitted to the base.
+        // Choosing between the two with that probability splits the energy without needing to trace both paths.
+        if Dielectric::reflectance(cos, refraction_ratio) > rand::thread_rng().gen() {
+            return Some(ScatterRecord::new(
+                &Ray::new(hit_record.point, Vec3::reflect(direction, hit_record.normal)),
+                Color::all(1.0)));
+        }
+
+        // Light scattered by the base has to pass through the coat again on the way out.
+        // The part that gets reflected back inside is treated as absorbed which darkens the base like a real coat does.
+        let base = self.base.scatter(ray, hit_record)?;
+        let cos_out = Vec3::dot(base.ray.direction.normalized(), hit_record.normal);
+        let transmitted = if cos_out > 0.0 { 1.0 - Dielectric::reflectance(f32::min(cos_out, 1.0), refraction_ratio) } else { 1.0 };
+        let scattered = ScatterRecord::new(&base.ray, base.attenuation * transmitted);
+        match base.pdf {
+            Some(_) => Some(scattered.with_pdf(self.pdf(ray, hit_record, base.ray.direction))),
+            None => Some(scattered)
+        }
+    }
+
+    // The coat itself is a perfect mirror so only the light that makes it through to the base and back out is left.
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let base = self.base.evaluate(ray, hit_record, direction);
+        if !hit_record.is_front_face {
+            return base;
+        }
+        let refraction_ratio = 1.0 / self.index_of_refraction;
+        let cos_in = Vec3::dot(direction.normalized(), hit_record.normal).clamp(0.0, 1.0);
+        let cos_out = Vec3::dot(-ray.direction.normalized(), hit_record.normal).clamp(0.0, 1.0);
+        let transmitted_in = 1.0 - Dielectric::reflectance(cos_in, refraction_ratio);
+        let transmitted_out = 1.0 - Dielectric::reflectance(cos_out, refraction_ratio);
+        base * transmitted_in * transmitted_out
+    }
+
+    // Only directions going through to the base can be chosen other than the mirror reflection.
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        let base = self.base.pdf(ray, hit_record, direction);
+        if !hit_record.is_front_face {
+            return base;
+        }
+        let cos = f32::min(Vec3::dot(-ray.direction.normalized(), hit_record.normal), 1.0);
+        (1.0 - Dielectric::reflectance(cos, 1.0 / self.index_of_refraction)) * base
+    }
+
+    fn color_emmited(&self, hit_record: &HitRecord) -> Color {
+        self.base.color_emmited(hit_record)
+    }
+}
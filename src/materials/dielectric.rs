@@ -1,7 +1,6 @@
 use crate::materials::{Material, ScatterRecord};
 use crate::ray::Ray;
-use crate::vec2::Vec2;
-use crate::vec3::{Vec3, Pt3, Color};
+use crate::vec3::Vec3;
 use crate::hittable_objects::HitRecord;
 use rand::Rng;
 
@@ -14,7 +13,7 @@ impl Dielectric {
         Dielectric{ index_of_refraction }
     }
 
-    fn reflectance(cosine: f32, index_of_refraction: f32) -> f32 {
+    pub fn reflectance(cosine: f32, index_of_refraction: f32) -> f32 {
         // Use Schlick's polynomial approximation for reflectance.
         let mut r0 = (1.0 - index_of_refraction) / (1.0 + index_of_refraction);
         r0 *= r0;
@@ -48,8 +47,4 @@ impl Material for Dielectric {
         };
         Some(ScatterRecord::new(&Ray::new(hit_record.point, direction), Vec3::all(1.0)))
     }
-
-    fn color_emmited(&self, _: Vec2, _: Pt3) -> Color {
-        Color::all(0.0)
-    }
 }
\ No newline at end of file
@@ -1,16 +1,28 @@
 use std::sync::Arc;
 
-use crate::{textures::Texture, ray::Ray, hittable_objects::HitRecord, vec3::{Color, Pt3}, vec2::Vec2};
+use crate::{textures::Texture, ray::Ray, hittable_objects::HitRecord, vec3::Color};
 
 use super::{Material, ScatterRecord};
 
 pub struct DiffuseLight {
-    pub emit: Arc<dyn Texture>
+    pub emit: Arc<dyn Texture>,
+    // Multiplies the texture so the same texture can be used for lights of different brightness.
+    pub strength: f32,
+    // One sided lights only emit from the front face. For example a light on the ceiling shouldn't light up the space above it.
+    pub two_sided: bool
 }
 
 impl DiffuseLight {
     pub fn new(emit: Arc<dyn Texture>) -> Self {
-        Self { emit }
+        Self::two_sided(emit, 1.0)
+    }
+
+    pub fn one_sided(emit: Arc<dyn Texture>, strength: f32) -> Self {
+        Self { emit, strength, two_sided: false }
+    }
+
+    pub fn two_sided(emit: Arc<dyn Texture>, strength: f32) -> Self {
+        Self { emit, strength, two_sided: true }
     }
 }
 
@@ -19,7 +31,10 @@ impl Material for DiffuseLight {
         None
     }
 
-    fn color_emmited(&self, uv: Vec2, hit_point: Pt3) -> Color {
-        self.emit.color(uv, hit_point)
+    fn color_emmited(&self, hit_record: &HitRecord) -> Color {
+        if !self.two_sided && !hit_record.is_front_face {
+            return Color::all(0.0);
+        }
+        self.strength * self.emit.color_at(hit_record)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use crate::{density_fields::DensityField, vec3::{Vec3, Color}, hittable_objects::HitRecord, ray::Ray};
+
+use super::{Material, ScatterRecord};
+
+// Phase function of a medium that also glows, like fire or the hot parts of an explosion.
+// Media are only hit at real particles, so the emission is what a single collision adds and parts of the medium
+// without any density don't glow. Fields from a simulation usually already have emission only where the smoke is.
+pub struct EmissiveVolume {
+    pub phase_function: Arc<dyn Material>,
+    // Brightness at each point.
+    pub emission: Arc<dyn DensityField>,
+    // Temperature in kelvin. When it's set the color of the light comes from it instead of the color.
+    pub temperature: Option<Arc<dyn DensityField>>,
+    pub color: Color,
+    // Multiplies the emission so the same field can be used for dimmer or brighter fire.
+    pub strength: f32
+}
+
+impl EmissiveVolume {
+    pub fn new(phase_function: Arc<dyn Material>, emission: Arc<dyn DensityField>, color: Color, strength: f32) -> Self {
+        Self { phase_function, emission, temperature: None, color, strength }
+    }
+
+    pub fn with_temperature(phase_function: Arc<dyn Material>, emission: Arc<dyn DensityField>, temperature: Arc<dyn DensityField>, strength: f32) -> Self {
+        Self { phase_function, emission, temperature: Some(temperature), color: Color::all(1.0), strength }
+    }
+
+    // Color of a black body at the temperature with the brightest channel scaled to 1. Planck's law evaluated
+    // at a single wavelength for each of the red, green and blue channels (610nm, 550nm and 465nm).
+    pub fn blackbody_color(temperature: f32) -> Color {
+        // Second radiation constant hc/k in meter kelvins.
+        const C2: f32 = 1.4388e-2;
+        const WAVELENGTHS: [f32; 3] = [610e-9, 550e-9, 465e-9];
+        if temperature <= 0.0 {
+            return Color::all(0.0);
+        }
+        // The constant factor of the law doesn't matter because of the scaling.
+        let [r, g, b] = WAVELENGTHS.map(|wavelength| 1.0 / (wavelength.powi(5) * f32::exp_m1(C2 / (wavelength * temperature))));
+        let max = r.max(g).max(b);
+        if max <= 0.0 || !max.is_finite() {
+            return Color::all(0.0);
+        }
+        Color::new(r / max, g / max, b / max)
+    }
+}
+
+impl Material for EmissiveVolume {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        self.phase_function.scatter(ray, hit_record)
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        self.phase_function.evaluate(ray, hit_record, direction)
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        self.phase_function.pdf(ray, hit_record, direction)
+    }
+
+    fn color_emmited(&self, hit_record: &HitRecord) -> Color {
+        let emission = self.strength * self.emission.density(hit_record.point);
+        if emission <= 0.0 {
+            return Color::all(0.0);
+        }
+        let color = match &self.temperature {
+            Some(temperature) => Self::blackbody_color(temperature.density(hit_record.point)),
+            None => self.color
+        };
+        emission * color
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blackbody_colors() {
+        // Embers are deep orange.
+        let ember = EmissiveVolume::blackbody_color(1500.0);
+        assert_eq!(ember.x, 1.0);
+        assert!(ember.y < 0.5 && ember.z < 0.1 && ember.y > ember.z);
+
+        // Around the temperature of daylight all the channels are close to each other.
+        let daylight = EmissiveVolume::blackbody_color(6500.0);
+        assert!(daylight.x > 0.75 && daylight.y > 0.75 && daylight.z > 0.75);
+
+        // Hotter is always bluer.
+        let temperatures = [1000.0, 2000.0, 3000.0, 5000.0, 6500.0, 10000.0, 20000.0];
+        for pair in temperatures.windows(2) {
+            let (cold, hot) = (EmissiveVolume::blackbody_color(pair[0]), EmissiveVolume::blackbody_color(pair[1]));
+            assert!(hot.z / hot.x > cold.z / cold.x);
+        }
+        assert!(EmissiveVolume::blackbody_color(0.0).is_near_zero());
+    }
+}
@@ -0,0 +1,64 @@
+use std::f32::consts::{PI, TAU};
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::{textures::Texture, vec3::{Vec3, Color}, hittable_objects::HitRecord, ray::Ray, onb::Onb};
+
+use super::{Material, ScatterRecord};
+
+// Phase function for media whose particles scatter more light forward or backward, like clouds and fog
+// which mostly scatter forward and make the sun look bright through them.
+pub struct HenyeyGreenstein {
+    pub albedo: Arc<dyn Texture>,
+    // Anisotropy in range <-1, 1>. Positive values scatter light forward, negative backward and 0 is the same as Isotropic.
+    pub g: f32
+}
+
+impl HenyeyGreenstein {
+    pub fn new(albedo: Arc<dyn Texture>, g: f32) -> Self {
+        // At exactly 1 or -1 the phase function is a delta function.
+        Self { albedo, g: g.clamp(-0.999, 0.999) }
+    }
+
+    // Probability density of scattering by an angle with the cosine from the direction the light travels in.
+    pub fn phase(cos: f32, g: f32) -> f32 {
+        let denominator = 1.0 + g * g - 2.0 * g * cos;
+        (1.0 - g * g) / (4.0 * PI * denominator * denominator.sqrt())
+    }
+
+    // Chooses the new direction with probability equal to the phase function.
+    pub fn sample(direction: Vec3, g: f32) -> Vec3 {
+        let (r1, r2): (f32, f32) = (rand::thread_rng().gen(), rand::thread_rng().gen());
+        // Inverse of the cumulative distribution of the cosine of the angle between the old and new direction.
+        let cos = if g.abs() < 1e-3 {
+            1.0 - 2.0 * r1
+        } else {
+            let s = (1.0 - g * g) / (1.0 - g + 2.0 * g * r1);
+            ((1.0 + g * g - s * s) / (2.0 * g)).clamp(-1.0, 1.0)
+        };
+        let sin = f32::sqrt(1.0 - cos * cos);
+        let phi = TAU * r2;
+        Onb::from_w(direction).local(Vec3::new(sin * phi.cos(), sin * phi.sin(), cos))
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        // Sampling exactly the phase function so only the albedo is left.
+        let direction = Self::sample(ray.direction.normalized(), self.g);
+        Some(ScatterRecord::new(
+            &Ray::new(hit_record.point, direction),
+            self.albedo.color_at(hit_record))
+            .with_pdf(self.pdf(ray, hit_record, direction)))
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let cos = Vec3::dot(ray.direction.normalized(), direction.normalized());
+        self.albedo.color_at(hit_record) * Self::phase(cos, self.g)
+    }
+
+    fn pdf(&self, ray: &Ray, _: &HitRecord, direction: Vec3) -> f32 {
+        Self::phase(Vec3::dot(ray.direction.normalized(), direction.normalized()), self.g)
+    }
+}
@@ -1,6 +1,7 @@
+use std::f32::consts::PI;
 use std::sync::Arc;
 
-use crate::{textures::Texture, vec3::{Color, Pt3, Vec3}, vec2::Vec2, hittable_objects::HitRecord, ray::Ray};
+use crate::{textures::Texture, vec3::{Vec3, Color}, hittable_objects::HitRecord, ray::Ray};
 
 use super::{Material, ScatterRecord};
 
@@ -18,10 +19,16 @@ impl Material for Isotropic {
     fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
         Some(ScatterRecord::new(
             &Ray::new(hit_record.point, Vec3::random_in_unit_sphere()), 
-            self.albedo.color(hit_record.texture_coord, hit_record.point)))
+            self.albedo.color_at(hit_record))
+            .with_pdf(1.0 / (4.0 * PI)))
     }
 
-    fn color_emmited(&self, uv: Vec2, hit_point: Pt3) -> Color {
-        Color::all(0.0)
+    // Scatters equally in all directions.
+    fn evaluate(&self, _: &Ray, hit_record: &HitRecord, _: Vec3) -> Color {
+        self.albedo.color_at(hit_record) / (4.0 * PI)
     }
-}
\ No newline at end of file
+
+    fn pdf(&self, _: &Ray, _: &HitRecord, _: Vec3) -> f32 {
+        1.0 / (4.0 * PI)
+    }
+}
@@ -1,11 +1,11 @@
+use std::f32::consts::PI;
 use std::sync::Arc;
 
 use crate::materials::{Material, ScatterRecord};
 use crate::ray::Ray;
 use crate::hittable_objects::HitRecord;
 use crate::textures::{Texture, SolidColor};
-use crate::vec2::Vec2;
-use crate::vec3::{Vec3, Pt3, Color};
+use crate::vec3::{Vec3, Color};
 
 pub struct Lambertian {
     pub albedo: Arc<dyn Texture>
@@ -22,7 +22,7 @@ impl Lambertian {
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
         // Generate random vector on the unit sphere centerted at the normal.
         // This generates a direction with distribution of cos(angle) so later the lambertian factor doesn't need to be applied.
         // Generating a uniformly distributed point on a sphere and then applying lambert's cosine law would probably have the same effect.
@@ -33,10 +33,16 @@ impl Material for Lambertian {
         let scatter_direction = if random_direction.is_near_zero() { hit_record.normal } else { random_direction };
         Some(ScatterRecord::new(
             &Ray::new(hit_record.point, scatter_direction), 
-            self.albedo.color(hit_record.texture_coord, hit_record.point)))
+            self.albedo.color_at(hit_record))
+            .with_pdf(self.pdf(ray, hit_record, scatter_direction)))
     }
 
-    fn color_emmited(&self, _: Vec2, _: Pt3) -> Color {
-        Color::all(0.0)
+    fn evaluate(&self, _: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let cos = Vec3::dot(direction.normalized(), hit_record.normal).max(0.0);
+        self.albedo.color_at(hit_record) * cos / PI
     }
-}
\ No newline at end of file
+
+    fn pdf(&self, _: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        Vec3::dot(direction.normalized(), hit_record.normal).max(0.0) / PI
+    }
+}
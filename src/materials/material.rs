@@ -1,22 +1,44 @@
 use crate::hittable_objects::HitRecord;
-use crate::vec2::Vec2;
-use crate::vec3::{Vec3, Color, Pt3};
+use crate::vec3::{Vec3, Color};
 use crate::ray::Ray;
 
 #[derive(Debug, Clone, Copy)]
 pub struct ScatterRecord {
     pub ray: Ray,
-    pub attenuation: Vec3
+    pub attenuation: Vec3,
+    // Probability density of the scattered direction with respect to solid angle. None when it was chosen from a delta
+    // distribution like a mirror, which can't be matched by sampling lights directly.
+    pub pdf: Option<f32>
 }
 
 impl ScatterRecord {
     pub fn new(ray: &Ray, attenuation: Vec3) -> ScatterRecord {
-        ScatterRecord{ ray: *ray, attenuation }
+        ScatterRecord{ ray: *ray, attenuation, pdf: None }
+    }
+
+    pub fn with_pdf(self, pdf: f32) -> ScatterRecord {
+        ScatterRecord{ pdf: Some(pdf), ..self }
     }
 }
 
 pub trait Material where Self: Send + Sync {
     fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord>;
-    // There is no way to provide a overridable default implementation.
-    fn color_emmited(&self, uv: Vec2, hit_point: Pt3) -> Color;
-}
\ No newline at end of file
+
+    // Reflected light for light arriving from the direction, the brdf multiplied by the cosine of the angle with the normal.
+    // Used for lights that can't be hit by scattered rays so they have to be sampled directly.
+    // Perfectly specular materials only reflect light from a single direction which is never the one of a light.
+    fn evaluate(&self, _ray: &Ray, _hit_record: &HitRecord, _direction: Vec3) -> Color {
+        Color::all(0.0)
+    }
+
+    // Probability density of scatter choosing the direction. Light that can be found both by sampling it directly and by
+    // scattering is weighted using both densities, so every material with evaluate has to return the density of its scatter.
+    fn pdf(&self, _ray: &Ray, _hit_record: &HitRecord, _direction: Vec3) -> f32 {
+        0.0
+    }
+
+    // Most materials don't emit any light.
+    fn color_emmited(&self, _hit_record: &HitRecord) -> Color {
+        Color::all(0.0)
+    }
+}
@@ -1,6 +1,5 @@
 use crate::materials::{Material, ScatterRecord};
-use crate::vec2::Vec2;
-use crate::vec3::{Vec3, Pt3, Color};
+use crate::vec3::Vec3;
 use crate::ray::Ray;
 use crate::hittable_objects::HitRecord;
 
@@ -25,8 +24,4 @@ impl Material for Metal {
             None
         }
     }
-
-    fn color_emmited(&self, _: Vec2, _: Pt3) -> Color {
-        Color::all(0.0)
-    }
 }
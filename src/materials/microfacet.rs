@@ -0,0 +1,149 @@
+use std::f32::consts::{PI, TAU};
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::materials::{Material, ScatterRecord};
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::hittable_objects::HitRecord;
+use crate::textures::{Texture, SolidColor};
+use crate::vec3::{Vec3, Color};
+
+// Rough conductor made of tiny perfect mirrors oriented according to the GGX distribution.
+// Different roughness along the tangent and the bitangent creates stretched highlights like on brushed metal.
+pub struct Microfacet {
+    // Reflectance at normal incidence.
+    pub albedo: Arc<dyn Texture>,
+    // Roughness along the tangent and the bitangent.
+    pub alpha_x: f32,
+    pub alpha_y: f32,
+    // Rotation of the tangent around the normal in turns so 1 is a full rotation.
+    pub rotation: Arc<dyn Texture>
+}
+
+impl Microfacet {
+    pub fn new(albedo: Arc<dyn Texture>, alpha_x: f32, alpha_y: f32, rotation: Arc<dyn Texture>) -> Self {
+        // Perfectly smooth surfaces would make the distribution a delta function.
+        const MIN_ALPHA: f32 = 1e-3;
+        Self { albedo, alpha_x: alpha_x.max(MIN_ALPHA), alpha_y: alpha_y.max(MIN_ALPHA), rotation }
+    }
+
+    pub fn from_color(albedo: Vec3, alpha_x: f32, alpha_y: f32) -> Self {
+        Self::new(Arc::new(SolidColor::new(albedo)), alpha_x, alpha_y, Arc::new(SolidColor::new(Color::all(0.0))))
+    }
+
+    fn lambda(&self, w: Vec3) -> f32 {
+        let tan2 = (self.alpha_x * self.alpha_x * w.x * w.x + self.alpha_y * self.alpha_y * w.y * w.y) / (w.z * w.z);
+        (-1.0 + f32::sqrt(1.0 + tan2)) / 2.0
+    }
+
+    // Samples only the microfacets visible from the direction (Heitz 2018).
+    // The direction is in the local space where the normal is the z axis.
+    fn sample_visible_normal(&self, to_viewer: Vec3) -> Vec3 {
+        let (r1, r2): (f32, f32) = (rand::thread_rng().gen(), rand::thread_rng().gen());
+        // Transforming to the space where the distribution is a hemisphere.
+        let v = Vec3::new(self.alpha_x * to_viewer.x, self.alpha_y * to_viewer.y, to_viewer.z).normalized();
+        let length_squared = v.x * v.x + v.y * v.y;
+        let t1 = if length_squared > 0.0 { Vec3::new(-v.y, v.x, 0.0) / length_squared.sqrt() } else { Vec3::new(1.0, 0.0, 0.0) };
+        let t2 = Vec3::cross(v, t1);
+
+        // Uniform point on a disk, warped so its projection covers only the visible half.
+        let r = r1.sqrt();
+        let phi = TAU * r2;
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1.0 + v.z);
+        let p2 = (1.0 - s) * f32::sqrt(1.0 - p1 * p1) + s * r * phi.sin();
+        let n = p1 * t1 + p2 * t2 + f32::sqrt(f32::max(0.0, 1.0 - p1 * p1 - p2 * p2)) * v;
+
+        Vec3::new(self.alpha_x * n.x, self.alpha_y * n.y, f32::max(0.0, n.z)).normalized()
+    }
+
+    fn frame(&self, hit_record: &HitRecord) -> Onb {
+        let normal = hit_record.normal;
+        let tangent = (hit_record.tangent - Vec3::dot(hit_record.tangent, normal) * normal).normalized();
+        let bitangent = Vec3::cross(normal, tangent);
+        let angle = TAU * self.rotation.value_at(hit_record);
+        let (sin, cos) = angle.sin_cos();
+        let u = cos * tangent + sin * bitangent;
+        Onb { u, v: Vec3::cross(normal, u), w: normal }
+    }
+
+    fn to_local(frame: &Onb, direction: Vec3) -> Vec3 {
+        Vec3::new(Vec3::dot(direction, frame.u), Vec3::dot(direction, frame.v), Vec3::dot(direction, frame.w))
+    }
+
+    // Density of microfacets oriented along the normal in local space.
+    fn distribution(&self, normal: Vec3) -> f32 {
+        let (x, y) = (normal.x / self.alpha_x, normal.y / self.alpha_y);
+        let denominator = x * x + y * y + normal.z * normal.z;
+        1.0 / (PI * self.alpha_x * self.alpha_y * denominator * denominator)
+    }
+
+    // Density of reflecting into the direction with visible normal sampling. The density of the visible normals
+    // is G1 * D * cos / cos_out and reflecting about the normal divides it by 4 * cos.
+    fn local_pdf(&self, to_viewer: Vec3, direction: Vec3) -> f32 {
+        if to_viewer.z <= 0.0 || direction.z <= 0.0 {
+            return 0.0;
+        }
+        let half_vector = (to_viewer + direction).normalized();
+        let masking = 1.0 / (1.0 + self.lambda(to_viewer));
+        masking * self.distribution(half_vector) / (4.0 * to_viewer.z)
+    }
+
+    fn fresnel(albedo: Color, cos: f32) -> Color {
+        // Schlick's approximation with the albedo as the reflectance at normal incidence.
+        albedo + f32::powf(1.0 - cos, 5.0) * (Color::all(1.0) - albedo)
+    }
+}
+
+impl Material for Microfacet {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let frame = self.frame(hit_record);
+        let to_viewer = Self::to_local(&frame, -ray.direction.normalized());
+        if to_viewer.z <= 0.0 {
+            return None;
+        }
+
+        let microfacet_normal = self.sample_visible_normal(to_viewer);
+        let direction = Vec3::reflect(-to_viewer, microfacet_normal);
+        // Reflected below the surface so it would have hit another microfacet.
+        if direction.z <= 0.0 {
+            return None;
+        }
+
+        let cos = f32::min(Vec3::dot(to_viewer, microfacet_normal), 1.0).max(0.0);
+        let fresnel = Self::fresnel(self.albedo.color_at(hit_record), cos);
+
+        // With visible normal sampling most of the terms cancel out and only the ratio
+        // of the masking-shadowing function and the masking function is left.
+        let masking = 1.0 + self.lambda(to_viewer);
+        let masking_shadowing = 1.0 + self.lambda(to_viewer) + self.lambda(direction);
+        let pdf = self.local_pdf(to_viewer, direction);
+        Some(ScatterRecord::new(
+            &Ray::new(hit_record.point, frame.local(direction)),
+            fresnel * (masking / masking_shadowing))
+            .with_pdf(pdf))
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let frame = self.frame(hit_record);
+        let to_viewer = Self::to_local(&frame, -ray.direction.normalized());
+        let direction = Self::to_local(&frame, direction.normalized());
+        if to_viewer.z <= 0.0 || direction.z <= 0.0 {
+            return Color::all(0.0);
+        }
+
+        // D * F * G / (4 * cos_out * cos_in) multiplied by cos_in.
+        let half_vector = (to_viewer + direction).normalized();
+        let cos = f32::min(Vec3::dot(to_viewer, half_vector), 1.0).max(0.0);
+        let fresnel = Self::fresnel(self.albedo.color_at(hit_record), cos);
+        let masking_shadowing = 1.0 / (1.0 + self.lambda(to_viewer) + self.lambda(direction));
+        fresnel * (self.distribution(half_vector) * masking_shadowing / (4.0 * to_viewer.z))
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        let frame = self.frame(hit_record);
+        self.local_pdf(Self::to_local(&frame, -ray.direction.normalized()), Self::to_local(&frame, direction.normalized()))
+    }
+}
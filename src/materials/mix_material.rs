@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::{textures::Texture, ray::Ray, hittable_objects::HitRecord, vec3::{Color, Vec3}};
+
+use super::{Material, ScatterRecord};
+
+pub struct MixMaterial {
+    pub first: Arc<dyn Material>,
+    pub second: Arc<dyn Material>,
+    // 0 selects only the first material and 1 only the second.
+    pub weight: Arc<dyn Texture>
+}
+
+impl MixMaterial {
+    pub fn new(first: Arc<dyn Material>, second: Arc<dyn Material>, weight: Arc<dyn Texture>) -> Self {
+        Self { first, second, weight }
+    }
+}
+
+impl Material for MixMaterial {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        // Picking one of the materials with probability equal to the weight gives on average the same result
+        // as scattering with both and blending the results, but only requires tracing a single ray.
+        let weight = self.weight.value_at(hit_record);
+        let scattered = if rand::thread_rng().gen::<f32>() < weight {
+            self.second.scatter(ray, hit_record)?
+        } else {
+            self.first.scatter(ray, hit_record)?
+        };
+        // The other material could have chosen the same direction too.
+        match scattered.pdf {
+            Some(_) => Some(scattered.with_pdf(self.pdf(ray, hit_record, scattered.ray.direction))),
+            None => Some(scattered)
+        }
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let weight = self.weight.value_at(hit_record);
+        (1.0 - weight) * self.first.evaluate(ray, hit_record, direction) + weight * self.second.evaluate(ray, hit_record, direction)
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        let weight = self.weight.value_at(hit_record);
+        (1.0 - weight) * self.first.pdf(ray, hit_record, direction) + weight * self.second.pdf(ray, hit_record, direction)
+    }
+
+    fn color_emmited(&self, hit_record: &HitRecord) -> Color {
+        let weight = self.weight.value_at(hit_record);
+        (1.0 - weight) * self.first.color_emmited(hit_record) + weight * self.second.color_emmited(hit_record)
+    }
+}
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use crate::{textures::Texture, ray::Ray, hittable_objects::HitRecord, vec3::{Color, Vec3}};
+
+use super::{Material, ScatterRecord};
+
+// Changes the normal the base material sees using a tangent space normal map.
+// The texture stores the normal's coordinates in the tangent, bitangent and normal basis mapped from <-1, 1> to <0, 1>.
+// Normal map images have to be loaded with ColorSpace::Linear.
+pub struct NormalMap {
+    pub base: Arc<dyn Material>,
+    pub normal_map: Arc<dyn Texture>,
+    pub strength: f32
+}
+
+impl NormalMap {
+    pub fn new(base: Arc<dyn Material>, normal_map: Arc<dyn Texture>, strength: f32) -> Self {
+        Self { base, normal_map, strength }
+    }
+
+    fn shading_normal(&self, hit_record: &HitRecord) -> Vec3 {
+        // The map describes the outside of the surface so the frame uses the outward normal and is flipped at the end.
+        let normal = if hit_record.is_front_face { hit_record.normal } else { -hit_record.normal };
+        // The tangents might not be perpendicular to the normal so they have to be orthogonalized first.
+        let tangent = (hit_record.tangent - Vec3::dot(hit_record.tangent, normal) * normal).normalized();
+        let bitangent = hit_record.bitangent - Vec3::dot(hit_record.bitangent, normal) * normal;
+        let bitangent = (bitangent - Vec3::dot(bitangent, tangent) * tangent).normalized();
+
+        let mapped = 2.0 * self.normal_map.color_at(hit_record) - Vec3::all(1.0);
+        let outward = (self.strength * mapped.x * tangent + self.strength * mapped.y * bitangent + mapped.z * normal).normalized();
+        if hit_record.is_front_face { outward } else { -outward }
+    }
+}
+
+impl Material for NormalMap {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let mut shading_record = hit_record.clone();
+        shading_record.normal = self.shading_normal(hit_record);
+        self.base.scatter(ray, &shading_record)
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let mut shading_record = hit_record.clone();
+        shading_record.normal = self.shading_normal(hit_record);
+        self.base.evaluate(ray, &shading_record, direction)
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        let mut shading_record = hit_record.clone();
+        shading_record.normal = self.shading_normal(hit_record);
+        self.base.pdf(ray, &shading_record, direction)
+    }
+
+    fn color_emmited(&self, hit_record: &HitRecord) -> Color {
+        self.base.color_emmited(hit_record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{textures::SolidColor, materials::Lambertian, vec2::Vec2};
+
+    fn normal_map(color: Color) -> NormalMap {
+        NormalMap::new(Arc::new(Lambertian::from_color(Color::all(0.5))), Arc::new(SolidColor::new(color)), 1.0)
+    }
+
+    // Hit of the z = 0 plane facing +z from the given side, with u going along x and v along y.
+    fn hit(material: Arc<dyn Material>, from_front: bool) -> HitRecord {
+        let direction = Vec3::new(0.0, 0.0, if from_front { -1.0 } else { 1.0 });
+        let ray = Ray::new(-direction, direction);
+        HitRecord::new(Vec3::all(0.0), &ray, Vec3::new(0.0, 0.0, 1.0), 1.0, Vec2::all(0.0), material)
+            .with_tangents(Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0))
+    }
+
+    #[test]
+    fn decodes_tangent_space() {
+        let flat = Arc::new(normal_map(Color::new(0.5, 0.5, 1.0)));
+        assert!((flat.shading_normal(&hit(flat.clone(), true)) - Vec3::new(0.0, 0.0, 1.0)).length() < 1e-5);
+
+        // Leaning halfway towards the tangent and the bitangent.
+        let tilted = Arc::new(normal_map(Color::new(0.75, 0.25, 1.0)));
+        let expected = Vec3::new(0.5, -0.5, 1.0).normalized();
+        assert!((tilted.shading_normal(&hit(tilted.clone(), true)) - expected).length() < 1e-5);
+    }
+
+    #[test]
+    fn back_faces_see_the_same_surface() {
+        let tilted = Arc::new(normal_map(Color::new(0.75, 0.25, 1.0)));
+        let front = tilted.shading_normal(&hit(tilted.clone(), true));
+        let back = tilted.shading_normal(&hit(tilted.clone(), false));
+        assert!((front + back).length() < 1e-5);
+    }
+}
@@ -0,0 +1,82 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use crate::materials::{Material, ScatterRecord};
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::hittable_objects::HitRecord;
+use crate::textures::{Texture, SolidColor};
+use crate::vec3::{Vec3, Color};
+
+// Rough diffuse surface made of tiny lambertian facets. Unlike lambertian surfaces rough surfaces
+// reflect more light back towards the light source which is why the full moon looks flat instead of like a ball.
+pub struct OrenNayar {
+    pub albedo: Arc<dyn Texture>,
+    // Standard deviation of the angle of the facets in radians. 0 is the same as lambertian.
+    pub sigma: f32
+}
+
+impl OrenNayar {
+    pub fn new(albedo: Arc<dyn Texture>, sigma: f32) -> Self {
+        Self { albedo, sigma }
+    }
+
+    pub fn from_color(albedo: Vec3, sigma: f32) -> Self {
+        Self::new(Arc::new(SolidColor::new(albedo)), sigma)
+    }
+
+    // The brdf multiplied by PI. Both directions point away from the surface.
+    fn reflectance(&self, hit_record: &HitRecord, to_viewer: Vec3, direction: Vec3) -> Color {
+        let normal = hit_record.normal;
+        let sigma2 = self.sigma * self.sigma;
+        let a = 1.0 - 0.5 * sigma2 / (sigma2 + 0.33);
+        let b = 0.45 * sigma2 / (sigma2 + 0.09);
+
+        let cos_in = f32::min(Vec3::dot(direction, normal), 1.0);
+        let cos_out = f32::min(Vec3::dot(to_viewer, normal), 1.0).max(1e-4);
+        let sin_in = f32::sqrt(1.0 - cos_in * cos_in);
+        let sin_out = f32::sqrt(1.0 - cos_out * cos_out);
+
+        // Cosine of the difference of the azimuthal angles found by projecting both directions onto the tangent plane.
+        let cos_phi_difference = if sin_in > 1e-4 && sin_out > 1e-4 {
+            let projected_in = (direction - cos_in * normal) / sin_in;
+            let projected_out = (to_viewer - cos_out * normal) / sin_out;
+            f32::max(Vec3::dot(projected_in, projected_out), 0.0)
+        } else {
+            0.0
+        };
+
+        // sin(alpha) * tan(beta) where alpha is the larger of the two angles and beta the smaller.
+        let sin_alpha_tan_beta = if cos_in > cos_out {
+            sin_out * sin_in / cos_in
+        } else {
+            sin_in * sin_out / cos_out
+        };
+
+        self.albedo.color_at(hit_record) * (a + b * cos_phi_difference * sin_alpha_tan_beta)
+    }
+}
+
+impl Material for OrenNayar {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let direction = Onb::from_w(hit_record.normal).local(Vec3::random_cosine_direction());
+        // The direction is cosine weighted so the cosine and 1 / PI from the brdf cancel out like for lambertian.
+        Some(ScatterRecord::new(
+            &Ray::new(hit_record.point, direction),
+            self.reflectance(hit_record, -ray.direction.normalized(), direction))
+            .with_pdf(self.pdf(ray, hit_record, direction)))
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let direction = direction.normalized();
+        let cos = Vec3::dot(direction, hit_record.normal);
+        if cos <= 0.0 {
+            return Color::all(0.0);
+        }
+        self.reflectance(hit_record, -ray.direction.normalized(), direction) * cos / PI
+    }
+
+    fn pdf(&self, _: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        Vec3::dot(direction.normalized(), hit_record.normal).max(0.0) / PI
+    }
+}
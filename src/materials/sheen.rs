@@ -0,0 +1,71 @@
+use std::f32::consts::{PI, TAU};
+use std::sync::Arc;
+
+use crate::materials::{Material, ScatterRecord};
+use crate::onb::Onb;
+use crate::ray::Ray;
+use crate::hittable_objects::HitRecord;
+use crate::textures::{Texture, SolidColor};
+use crate::vec3::{Vec3, Color};
+
+// Diffuse surface with a sheen layer for cloth like velvet or satin. The fibers sticking out of the surface
+// reflect light mostly at grazing angles which creates a bright rim around the edges.
+pub struct Sheen {
+    pub albedo: Arc<dyn Texture>,
+    pub sheen_color: Arc<dyn Texture>,
+    // In range <0, 1>. Low values make the sheen concentrated at the edges.
+    pub roughness: f32
+}
+
+impl Sheen {
+    pub fn new(albedo: Arc<dyn Texture>, sheen_color: Arc<dyn Texture>, roughness: f32) -> Self {
+        Self { albedo, sheen_color, roughness: roughness.clamp(0.01, 1.0) }
+    }
+
+    pub fn from_colors(albedo: Vec3, sheen_color: Vec3, roughness: f32) -> Self {
+        Self::new(Arc::new(SolidColor::new(albedo)), Arc::new(SolidColor::new(sheen_color)), roughness)
+    }
+
+    // The brdf multiplied by PI. Both directions point away from the surface.
+    fn reflectance(&self, hit_record: &HitRecord, to_viewer: Vec3, direction: Vec3) -> Color {
+        let normal = hit_record.normal;
+        let cos_in = f32::min(Vec3::dot(direction, normal), 1.0);
+        let cos_out = f32::min(Vec3::dot(to_viewer, normal), 1.0).max(1e-4);
+        let half_vector = (direction + to_viewer).normalized();
+        let cos_half = f32::min(Vec3::dot(half_vector, normal), 1.0);
+        let sin_half = f32::sqrt(1.0 - cos_half * cos_half);
+
+        // Charlie distribution of the fibers (Estevez and Kulla) with Ashikhmin's visibility term.
+        let alpha = self.roughness * self.roughness;
+        let distribution = (2.0 + 1.0 / alpha) * sin_half.powf(1.0 / alpha) / TAU;
+        let visibility = 1.0 / (4.0 * (cos_in + cos_out - cos_in * cos_out));
+
+        // Lambertian base with the sheen on top, both multiplied by PI.
+        let albedo = self.albedo.color_at(hit_record);
+        let sheen = self.sheen_color.color_at(hit_record);
+        albedo + PI * distribution * visibility * sheen
+    }
+}
+
+impl Material for Sheen {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let direction = Onb::from_w(hit_record.normal).local(Vec3::random_cosine_direction());
+        Some(ScatterRecord::new(
+            &Ray::new(hit_record.point, direction),
+            self.reflectance(hit_record, -ray.direction.normalized(), direction))
+            .with_pdf(self.pdf(ray, hit_record, direction)))
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        let direction = direction.normalized();
+        let cos = Vec3::dot(direction, hit_record.normal);
+        if cos <= 0.0 {
+            return Color::all(0.0);
+        }
+        self.reflectance(hit_record, -ray.direction.normalized(), direction) * cos / PI
+    }
+
+    fn pdf(&self, _: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        Vec3::dot(direction.normalized(), hit_record.normal).max(0.0) / PI
+    }
+}
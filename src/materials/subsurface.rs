@@ -0,0 +1,102 @@
+use rand::Rng;
+
+use crate::{ray::Ray, hittable_objects::HitRecord, vec3::{Color, Vec3}};
+
+use super::{Material, ScatterRecord, Dielectric, HenyeyGreenstein};
+
+// Light enters the object through a dielectric boundary and then does a random walk inside of it until it leaves.
+// The object has to be closed so every ray that enters it later hits the inside of the boundary.
+pub struct Subsurface {
+    boundary: Dielectric,
+    // Average distance light travels inside before hitting a particle. Separate for each channel,
+    // for example skin has a much longer mean free path for red light than for blue.
+    pub mean_free_path: Color,
+    // Probability of light being scattered instead of absorbed when it hits a particle.
+    pub albedo: Color,
+    // Henyey-Greenstein anisotropy. Positive values scatter light forward, negative backward and 0 is uniform.
+    pub anisotropy: f32
+}
+
+impl Subsurface {
+    pub fn new(albedo: Color, mean_free_path: Color, anisotropy: f32, index_of_refraction: f32) -> Self {
+        Self { boundary: Dielectric::new(index_of_refraction), mean_free_path, albedo, anisotropy }
+    }
+}
+
+impl Material for Subsurface {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        // Coming from the outside so the light only interacts with the boundary.
+        if hit_record.is_front_face {
+            return self.boundary.scatter(ray, hit_record);
+        }
+
+        // The ray started inside at the last scattering event or where it entered, so the segment up to the hit is inside the medium.
+        let ray_length = ray.direction.length();
+        let direction = ray.direction / ray_length;
+        let distance_to_boundary = hit_record.t * ray_length;
+        let extinction = Vec3::all(1.0) / self.mean_free_path;
+        let transmittance = |distance: f32| (-distance * extinction).applied(f32::exp);
+        let average = |v: Vec3| (v.x + v.y + v.z) / 3.0;
+
+        // The distance is sampled using the extinction of a randomly chosen channel. To keep the result unbiased
+        // for all channels the weight divides by the average of the probability densities of the channels.
+        let channel = rand::thread_rng().gen_range(0..3);
+        let distance = -f32::ln(1.0 - rand::thread_rng().gen::<f32>()) / extinction[channel];
+
+        if distance < distance_to_boundary {
+            let density = extinction * transmittance(distance);
+            let weight = self.albedo * density / average(density);
+            let point = ray.origin + distance * direction;
+            return Some(ScatterRecord::new(
+                &Ray::new(point, HenyeyGreenstein::sample(direction, self.anisotropy)),
+                weight));
+        }
+
+        // Reached the boundary without scattering. The probability of that is the transmittance.
+        let probability = transmittance(distance_to_boundary);
+        let weight = probability / average(probability);
+        let scattered = self.boundary.scatter(ray, hit_record)?;
+        Some(ScatterRecord::new(&scattered.ray, scattered.attenuation * weight))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::vec2::Vec2;
+
+    // Average weight of one step of the walk starting inside and heading to the boundary a distance away.
+    fn average_weight(material: Arc<Subsurface>, distance: f32) -> Color {
+        const SAMPLES: usize = 200_000;
+        let ray = Ray::new(Vec3::all(0.0), Vec3::new(1.0, 0.0, 0.0));
+        let record = HitRecord::new(Vec3::new(distance, 0.0, 0.0), &ray, Vec3::new(1.0, 0.0, 0.0), distance, Vec2::all(0.0), material.clone());
+        assert!(!record.is_front_face);
+        let mut total = Color::all(0.0);
+        for _ in 0..SAMPLES {
+            total += material.scatter(&ray, &record).unwrap().attenuation;
+        }
+        total / SAMPLES as f32
+    }
+
+    #[test]
+    fn walk_conserves_energy() {
+        let mean_free_path = Color::new(0.5, 1.0, 2.0);
+        // Without absorption the light is only redirected. Every channel has to stay at 1 even though
+        // the distances are sampled from all of them.
+        let weight = average_weight(Arc::new(Subsurface::new(Color::all(1.0), mean_free_path, 0.0, 1.5)), 1.0);
+        for i in 0..3 {
+            assert!((weight[i] - 1.0).abs() < 0.02, "channel {} has {}", i, weight[i]);
+        }
+
+        // Particles absorb part of the light that hits them, light that reaches the boundary is untouched.
+        let albedo = Color::new(0.8, 0.5, 0.2);
+        let weight = average_weight(Arc::new(Subsurface::new(albedo, mean_free_path, 0.3, 1.5)), 1.0);
+        for i in 0..3 {
+            let reaches_boundary = f32::exp(-1.0 / mean_free_path[i]);
+            let expected = albedo[i] * (1.0 - reaches_boundary) + reaches_boundary;
+            assert!((weight[i] - expected).abs() < 0.02, "channel {} has {} instead of {}", i, weight[i], expected);
+            assert!(weight[i] <= 1.0);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::{textures::{Texture, SolidColor}, ray::Ray, hittable_objects::HitRecord, vec3::{Color, Vec3}};
+
+use super::{Material, ScatterRecord};
+
+// Wavelengths in nanometers used to approximate the spectrum with the red, green and blue channels.
+const WAVELENGTHS: [f32; 3] = [650.0, 510.0, 475.0];
+
+// What is below the film. The reflectance of the film already includes the reflection from the top of the base,
+// so only the light that isn't reflected is left for the base.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilmBase {
+    // Transparent like water or glass. The rest of the light refracts into the base.
+    Dielectric,
+    // Opaque like a metal. The rest of the light is absorbed.
+    Conductor
+}
+
+// A thin transparent layer on top of another material like a soap bubble, an oil slick or anodised metal.
+// Light reflected from the top and the bottom of the film interferes and because the difference in the distance traveled
+// depends on the wavelength some colors get amplified and some get cancelled out.
+pub struct ThinFilm {
+    // Only scatters the light coming from inside of it, everything hitting the film from outside is handled by the film.
+    pub base: Arc<dyn Material>,
+    pub base_kind: FilmBase,
+    // In nanometers.
+    pub thickness: Arc<dyn Texture>,
+    pub film_index_of_refraction: f32,
+    // Index of refraction of what is below the film. For conductors some large value approximates them well enough.
+    pub base_index_of_refraction: f32
+}
+
+impl ThinFilm {
+    pub fn new(base: Arc<dyn Material>, base_kind: FilmBase, thickness: Arc<dyn Texture>, film_index_of_refraction: f32, base_index_of_refraction: f32) -> Self {
+        Self { base, base_kind, thickness, film_index_of_refraction, base_index_of_refraction }
+    }
+
+    pub fn from_thickness(base: Arc<dyn Material>, base_kind: FilmBase, thickness: f32, film_index_of_refraction: f32, base_index_of_refraction: f32) -> Self {
+        Self::new(base, base_kind, Arc::new(SolidColor::new(Color::all(thickness))), film_index_of_refraction, base_index_of_refraction)
+    }
+
+    // Fresnel amplitude coefficients for s and p polarized light.
+    fn fresnel_amplitudes(n_i: f32, cos_i: f32, n_t: f32, cos_t: f32) -> (f32, f32) {
+        let s = (n_i * cos_i - n_t * cos_t) / (n_i * cos_i + n_t * cos_t);
+        let p = (n_t * cos_i - n_i * cos_t) / (n_t * cos_i + n_i * cos_t);
+        (s, p)
+    }
+
+    fn reflectance(&self, cos: f32, thickness: f32) -> Color {
+        let (n1, n2, n3) = (1.0, self.film_index_of_refraction, self.base_index_of_refraction);
+        // Snell's law for the angles inside of the film and the base.
+        let sin1 = f32::sqrt(1.0 - cos * cos);
+        let cos2 = f32::sqrt(f32::max(1.0 - (n1 * sin1 / n2).powi(2), 0.0));
+        let sin3 = n1 * sin1 / n3;
+        let (r12_s, r12_p) = Self::fresnel_amplitudes(n1, cos, n2, cos2);
+        // Total internal reflection at the bottom of the film reflects everything. The phase change is ignored.
+        let (r23_s, r23_p) = if sin3 >= 1.0 {
+            (1.0, 1.0)
+        } else {
+            Self::fresnel_amplitudes(n2, cos2, n3, f32::sqrt(1.0 - sin3 * sin3))
+        };
+
+        // Sum of the amplitudes of all the rays reflected inside of the film (Airy formula).
+        // The phase difference between consecutive rays comes from the extra distance traveled inside of the film.
+        let airy = |r12: f32, r23: f32, phase: f32| {
+            let interference = 2.0 * r12 * r23 * phase.cos();
+            (r12 * r12 + r23 * r23 + interference) / (1.0 + r12 * r12 * r23 * r23 + interference)
+        };
+
+        let mut reflectance = Color::all(0.0);
+        for (i, wavelength) in WAVELENGTHS.iter().enumerate() {
+            let phase = 4.0 * std::f32::consts::PI * n2 * thickness * cos2 / wavelength;
+            // Unpolarized light is an average of both polarizations.
+            reflectance[i] = ((airy(r12_s, r23_s, phase) + airy(r12_p, r23_p, phase)) / 2.0).clamp(0.0, 1.0);
+        }
+        reflectance
+    }
+}
+
+impl Material for ThinFilm {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        if !hit_record.is_front_face {
+            return self.base.scatter(ray, hit_record);
+        }
+
+        let direction = ray.direction.normalized();
+        let cos = f32::min(Vec3::dot(-direction, hit_record.normal), 1.0);
+        let thickness = self.thickness.value_at(hit_record);
+        let reflectance = self.reflectance(cos, thickness);
+        let reflected = Ray::new(hit_record.point, Vec3::reflect(direction, hit_record.normal));
+        if self.base_kind == FilmBase::Conductor {
+            return Some(ScatterRecord::new(&reflected, reflectance));
+        }
+
+        // Choose between the reflection from the film and the light transmitted to the base using the average reflectance.
+        // Dividing by the probability keeps the colors of each path correct. The film is too thin to move the ray
+        // so it refracts as if it went straight from the air into the base.
+        let probability = (reflectance.x + reflectance.y + reflectance.z) / 3.0;
+        if probability > rand::thread_rng().gen() {
+            Some(ScatterRecord::new(&reflected, reflectance / probability))
+        } else {
+            let refracted = Vec3::refract(direction, hit_record.normal, 1.0 / self.base_index_of_refraction);
+            Some(ScatterRecord::new(&Ray::new(hit_record.point, refracted), (Color::all(1.0) - reflectance) / (1.0 - probability)))
+        }
+    }
+
+    // From outside both the reflection and the refraction are perfectly specular so lights can't be sampled directly.
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Color {
+        if hit_record.is_front_face { Color::all(0.0) } else { self.base.evaluate(ray, hit_record, direction) }
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        if hit_record.is_front_face { 0.0 } else { self.base.pdf(ray, hit_record, direction) }
+    }
+
+    fn color_emmited(&self, hit_record: &HitRecord) -> Color {
+        self.base.color_emmited(hit_record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{materials::{Dielectric, Metal}, vec2::Vec2};
+
+    fn hit(film: Arc<dyn Material>, direction: Vec3) -> (Ray, HitRecord) {
+        let ray = Ray::new(Vec3::new(0.0, 1.0, 0.0) - direction, direction);
+        let record = HitRecord::new(Vec3::new(0.0, 1.0, 0.0), &ray, Vec3::new(0.0, 1.0, 0.0), 1.0, Vec2::all(0.0), film);
+        (ray, record)
+    }
+
+    #[test]
+    fn dielectric_base_conserves_energy() {
+        let film = Arc::new(ThinFilm::from_thickness(Arc::new(Dielectric::new(1.33)), FilmBase::Dielectric, 400.0, 1.5, 1.33));
+        let direction = Vec3::new(0.6, -0.8, 0.0);
+        let (ray, record) = hit(film.clone(), direction);
+        let reflectance = film.reflectance(0.8, 400.0);
+
+        // On average the reflected and the refracted light add up to all of it, the base doesn't reflect anything again.
+        const SAMPLES: usize = 100_000;
+        let (mut reflected, mut refracted) = (Color::all(0.0), Color::all(0.0));
+        for _ in 0..SAMPLES {
+            let scattered = film.scatter(&ray, &record).unwrap();
+            assert!(scattered.pdf.is_none());
+            if scattered.ray.direction.y > 0.0 {
+                reflected += scattered.attenuation;
+            } else {
+                // Snell's law from air straight into the base.
+                assert!((scattered.ray.direction.normalized().x - 0.6 / 1.33).abs() < 1e-4);
+                refracted += scattered.attenuation;
+            }
+        }
+        let (reflected, refracted) = (reflected / SAMPLES as f32, refracted / SAMPLES as f32);
+        for i in 0..3 {
+            assert!((reflected[i] - reflectance[i]).abs() < 0.02);
+            assert!((reflected[i] + refracted[i] - 1.0).abs() < 0.02);
+        }
+        assert!(film.evaluate(&ray, &record, Vec3::new(0.0, 1.0, 0.0)).is_near_zero());
+    }
+
+    #[test]
+    fn conductor_base_only_reflects_the_film() {
+        let film = Arc::new(ThinFilm::from_thickness(Arc::new(Metal::new(&Color::all(0.8), 0.0)), FilmBase::Conductor, 300.0, 1.6, 10.0));
+        let direction = Vec3::new(0.6, -0.8, 0.0);
+        let (ray, record) = hit(film.clone(), direction);
+        let reflectance = film.reflectance(0.8, 300.0);
+        for _ in 0..100 {
+            let scattered = film.scatter(&ray, &record).unwrap();
+            assert!((scattered.ray.direction - Vec3::new(0.6, 0.8, 0.0)).length() < 1e-5);
+            assert!((scattered.attenuation - reflectance).length() < 1e-6);
+        }
+    }
+}
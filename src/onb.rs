@@ -0,0 +1,24 @@
+use crate::vec3::Vec3;
+
+// Orthonormal basis. Used to convert directions generated around the z axis into directions around an arbitrary vector.
+#[derive(Debug, Clone, Copy)]
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3
+}
+
+impl Onb {
+    pub fn from_w(w: Vec3) -> Self {
+        let w = w.normalized();
+        // Any vector that isn't parallel to w can be used to create the other axes.
+        let a = if w.x.abs() > 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+        let v = Vec3::cross(w, a).normalized();
+        let u = Vec3::cross(w, v);
+        Self { u, v, w }
+    }
+
+    pub fn local(&self, a: Vec3) -> Vec3 {
+        a.x * self.u + a.y * self.v + a.z * self.w
+    }
+}
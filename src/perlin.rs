@@ -2,51 +2,161 @@ use rand::Rng;
 
 use crate::vec3::Vec3;
 
-const VALUE_COUNT: usize = 256;
+const POINT_COUNT: usize = 256;
 
 pub struct Perlin {
-    random: [f32; VALUE_COUNT],
-    permutations_x: [usize; VALUE_COUNT],
-    permutations_y: [usize; VALUE_COUNT],
-    permutations_z: [usize; VALUE_COUNT]
+    gradients: [Vec3; POINT_COUNT],
+    permutations_x: [usize; POINT_COUNT],
+    permutations_y: [usize; POINT_COUNT],
+    permutations_z: [usize; POINT_COUNT]
+}
+
+// Parameters of fractal brownian motion. Octaves are layers of noise each one with the frequency multiplied
+// by the lacunarity and the amplitude multiplied by the gain compared to the previous one.
+#[derive(Debug, Clone, Copy)]
+pub struct Turbulence {
+    pub octaves: usize,
+    pub lacunarity: f32,
+    pub gain: f32
+}
+
+impl Default for Turbulence {
+    fn default() -> Self {
+        Self { octaves: 7, lacunarity: 2.0, gain: 0.5 }
+    }
 }
 
 impl Perlin {
     pub fn new() -> Self {
-        let mut random = [0.0f32; VALUE_COUNT];
-        for item in &mut random {
-            *item = rand::thread_rng().gen_range(0.0..1.0);
+        let mut gradients = [Vec3::all(0.0); POINT_COUNT];
+        for item in &mut gradients {
+            *item = Vec3::random_unit();
         }
 
         let generate_permutations = || {
-            let mut permutations = [0; VALUE_COUNT];
-            for item in &mut permutations {
-                *item = rand::thread_rng().gen_range(0..VALUE_COUNT);
+            let mut permutations = [0; POINT_COUNT];
+            for (i, item) in permutations.iter_mut().enumerate() {
+                *item = i;
             }
-            for i in 0..permutations.len() {
-                let target = rand::thread_rng().gen_range(0..VALUE_COUNT);
+            // Fisher-Yates shuffle.
+            for i in (1..permutations.len()).rev() {
+                let target = rand::thread_rng().gen_range(0..=i);
                 permutations.swap(i, target);
             }
             permutations
         };
         Perlin{
-            random, 
+            gradients,
             permutations_x: generate_permutations(),
             permutations_y: generate_permutations(),
             permutations_z: generate_permutations()
         }
     }
 
-    pub fn get(&self, p: Vec3) -> f32 {
-        const SCALE: f32 = 4.0;
-        // If the language allows conversion from float to unsigned int that allows underflow 
-        // a bitmask can be used because it will do modulo without the need for abs.
-        unsafe {
-            *self.random.get_unchecked(
-                self.permutations_x.get_unchecked((SCALE * p.x.abs()) as usize % VALUE_COUNT) ^
-                self.permutations_y.get_unchecked((SCALE * p.y.abs()) as usize % VALUE_COUNT) ^
-                self.permutations_z.get_unchecked((SCALE * p.z.abs()) as usize % VALUE_COUNT)
-            )
+    // Returns values in range <-1, 1>.
+    pub fn noise(&self, p: Vec3) -> f32 {
+        let (floor_x, floor_y, floor_z) = (p.x.floor(), p.y.floor(), p.z.floor());
+        let (u, v, w) = (p.x - floor_x, p.y - floor_y, p.z - floor_z);
+        // Casting to a signed int first so negative coordinates also wrap around when using the bitmask.
+        let (i, j, k) = (floor_x as i32, floor_y as i32, floor_z as i32);
+        let index = |n: i32| (n & (POINT_COUNT as i32 - 1)) as usize;
+
+        // Interpolating with the hermite cubic instead of linearly removes the visible grid artifacts
+        // because the derivative of the weight is 0 at the corners of the cell.
+        let hermite = |t: f32| t * t * (3.0 - 2.0 * t);
+        let (uu, vv, ww) = (hermite(u), hermite(v), hermite(w));
+
+        let mut accumulated = 0.0;
+        for di in 0..2 {
+            for dj in 0..2 {
+                for dk in 0..2 {
+                    let gradient = self.gradients[
+                        self.permutations_x[index(i + di)] ^
+                        self.permutations_y[index(j + dj)] ^
+                        self.permutations_z[index(k + dk)]
+                    ];
+                    let (di, dj, dk) = (di as f32, dj as f32, dk as f32);
+                    // Each corner contributes the distance along its random gradient so the value changes smoothly
+                    // between the corners instead of jumping between random values.
+                    let offset = Vec3::new(u - di, v - dj, w - dk);
+                    accumulated += (di * uu + (1.0 - di) * (1.0 - uu))
+                        * (dj * vv + (1.0 - dj) * (1.0 - vv))
+                        * (dk * ww + (1.0 - dk) * (1.0 - ww))
+                        * Vec3::dot(gradient, offset);
+                }
+            }
+        }
+        accumulated
+    }
+
+    // Sum of octaves of the noise. Returns values roughly in range <-1, 1>.
+    pub fn fbm(&self, p: Vec3, turbulence: Turbulence) -> f32 {
+        self.sum_octaves(p, turbulence, |n| n)
+    }
+
+    // Sum of the absolute values of octaves of the noise. The absolute value creates sharp creases
+    // which look like turbulent flow. Returns values roughly in range <0, 1>.
+    pub fn turbulence(&self, p: Vec3, turbulence: Turbulence) -> f32 {
+        self.sum_octaves(p, turbulence, f32::abs)
+    }
+
+    fn sum_octaves(&self, p: Vec3, Turbulence { octaves, lacunarity, gain }: Turbulence, octave: fn(f32) -> f32) -> f32 {
+        let mut accumulated = 0.0;
+        let mut point = p;
+        let mut amplitude = 1.0;
+        let mut total_amplitude = 0.0;
+        for _ in 0..octaves {
+            accumulated += amplitude * octave(self.noise(point));
+            total_amplitude += amplitude;
+            amplitude *= gain;
+            point = point * lacunarity;
+        }
+        if total_amplitude > 0.0 { accumulated / total_amplitude } else { 0.0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_point() -> Vec3 {
+        let mut rng = rand::thread_rng();
+        Vec3::new(rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0))
+    }
+
+    #[test]
+    fn output_range() {
+        let perlin = Perlin::new();
+        for _ in 0..10_000 {
+            let p = random_point();
+            assert!((-1.0..=1.0).contains(&perlin.noise(p)));
+            assert!((-1.0..=1.0).contains(&perlin.fbm(p, Turbulence::default())));
+            assert!((0.0..=1.0).contains(&perlin.turbulence(p, Turbulence::default())));
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn zero_at_lattice_points() {
+        let perlin = Perlin::new();
+        for x in -3..3 {
+            for y in -3..3 {
+                for z in -3..3 {
+                    assert!(perlin.noise(Vec3::new(x as f32, y as f32, z as f32)).abs() < 1e-6);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn deterministic() {
+        let perlin = Perlin::new();
+        for _ in 0..1000 {
+            let p = random_point();
+            assert_eq!(perlin.noise(p), perlin.noise(p));
+            assert_eq!(perlin.fbm(p, Turbulence::default()), perlin.fbm(p, Turbulence::default()));
+        }
+        // The lattice repeats after POINT_COUNT cells.
+        let p = Vec3::new(0.3, 1.7, -2.2);
+        assert!((perlin.noise(p) - perlin.noise(p + Vec3::all(POINT_COUNT as f32))).abs() < 1e-4);
+    }
+}
@@ -4,15 +4,24 @@ use crate::vec3::Vec3;
 pub struct Ray {
     pub origin: Vec3,
     // Doesn't have to be normalized.
-    pub direction: Vec3
+    pub direction: Vec3,
+    // The ray is treated as a cone covering the area of a pixel so textures know how much detail they need to show.
+    // Width of the cone at the origin.
+    pub cone_width: f32,
+    // How much the width grows per unit of distance traveled.
+    pub spread: f32
 }
 
 impl Ray {
     pub fn new(origin: Vec3, direction: Vec3) -> Ray {
-        Ray{ origin: origin, direction: direction }
+        Ray{ origin, direction, cone_width: 0.0, spread: 0.0 }
     }
 
     pub fn at(&self, t: f32) -> Vec3 {
         self.origin + self.direction * t
     }
-}
\ No newline at end of file
+
+    pub fn cone_width_at(&self, t: f32) -> f32 {
+        self.cone_width + self.spread * t * self.direction.length()
+    }
+}
@@ -5,21 +5,33 @@ use std::{fs::File};
 use std::io::{prelude::*, BufWriter};
 use std::path::Path;
 
-use crate::vec3::{Vec3, Color};
+use crate::vec3::{Vec3, Pt3, Color};
 use crate::ray::Ray;
-use crate::camera::Camera;
+use crate::cameras::*;
 use crate::materials::*;
 use crate::hittable_objects::*;
+use crate::environments::Environment;
+use crate::lights::LightSampler;
+use crate::height_fog::HeightFog;
 
 use rand::Rng;
 
 pub struct Scene {
     pub objects: Arc<dyn Hittable>,
+    // Lights sampled directly. Most of them aren't objects, except for area lights which have to be in both.
+    pub lights: Arc<dyn LightSampler>,
+    pub projection: Projection,
     pub look_from: Vec3,
     pub look_at: Vec3,
     pub vertical_fov: f32,
     pub aperture: f32,
-    pub background_color: Color,
+    // Light coming from everything around the scene. Seen by rays that don't hit anything.
+    pub environment: Arc<dyn Environment>,
+    // What the camera sees directly when it's different from the environment that lights the scene. For example a studio
+    // lighting environment behind a plain backdrop. Reflections and refractions still see the lighting environment.
+    pub camera_environment: Option<Arc<dyn Environment>>,
+    // Haze around everything in the scene. Every ray can scatter in it before reaching a surface or the environment.
+    pub fog: Option<HeightFog>,
     pub focus_distance: f32,
 }
 
@@ -33,20 +45,19 @@ pub fn run_raytracer(out_path: &Path, scene: Scene, aspect_ratio: f32) {
     });
 
     let image_width: usize = 400;
-    let image_height: usize = ((image_width as f32) / aspect_ratio) as usize;
+    // Panoramas replace the aspect ratio with the one they need. The width is rounded so every face of a cube map
+    // is a whole number of pixels.
+    let (image_width, image_height) = match scene.projection.required_aspect_ratio() {
+        Some(ratio) => ((image_width / ratio) * ratio, image_width / ratio),
+        None => (image_width, ((image_width as f32) / aspect_ratio) as usize)
+    };
     let samples_per_pixel = 500;
     let max_bounces: usize = 50;
 
     let scene = Arc::new(scene);
 
-    let camera = Arc::new(Camera::new(
-        scene.look_from, 
-        scene.look_at, 
-        scene.vertical_fov, 
-        aspect_ratio, 
-        scene.aperture, 
-        scene.focus_distance));
-        
+    let camera = create_camera(&scene, image_width, image_height);
+
     let start = Instant::now(); 
 
     let thread_count = num_cpus::get();
@@ -99,6 +110,29 @@ pub fn run_raytracer(out_path: &Path, scene: Scene, aspect_ratio: f32) {
     println!("took {}ms", start.elapsed().as_millis());
 }
 
+fn create_camera(scene: &Scene, image_width: usize, image_height: usize) -> Arc<dyn Camera> {
+    if let Some(ratio) = scene.projection.required_aspect_ratio() {
+        assert_eq!(image_width, ratio * image_height, "{:?} needs an image {} times as wide as it's high", scene.projection, ratio);
+    }
+    let aspect_ratio = image_width as f32 / image_height as f32;
+    match scene.projection {
+        Projection::Perspective => Arc::new(PerspectiveCamera::new(
+            scene.look_from,
+            scene.look_at,
+            scene.vertical_fov,
+            aspect_ratio,
+            scene.aperture,
+            scene.focus_distance,
+            image_height)),
+        Projection::Orthographic { view_height } =>
+            Arc::new(OrthographicCamera::new(scene.look_from, scene.look_at, view_height, aspect_ratio, image_height)),
+        Projection::Fisheye { fov, mapping } =>
+            Arc::new(FisheyeCamera::new(scene.look_from, scene.look_at, fov, mapping, aspect_ratio, image_height)),
+        Projection::Equirectangular => Arc::new(EquirectangularCamera::new(scene.look_from, scene.look_at, image_height)),
+        Projection::CubeMap => Arc::new(CubeMapCamera::new(scene.look_from, image_height))
+    }
+}
+
 fn run(
     image_width: usize, 
     image_height: usize, 
@@ -106,7 +140,7 @@ fn run(
     slice_height: usize, 
     samples_per_pixel: usize, 
     max_bounces: usize, 
-    camera: &Arc<Camera>, 
+    camera: &Arc<dyn Camera>, 
     scene: &Arc<Scene>, 
     finished_rows: &Arc<AtomicUsize>
 ) -> Vec<u8> {
@@ -118,9 +152,12 @@ fn run(
             let mut color = Color::all(0.0);
             for _ in 0..samples_per_pixel {
                 // TODO: Try euler integration.
-                let u: f32 = (x + rand::thread_rng().gen::<f32>()) / ((image_width - 1) as f32);
-                let v: f32 = (y + rand::thread_rng().gen::<f32>()) / ((image_height - 1) as f32);
-                color += ray_color(&camera.ray(u, v), &scene.objects, scene.background_color, max_bounces);
+                // Dividing by the size so the pixels together cover exactly <0, 1>. Cube map faces start at whole pixels.
+                let u: f32 = (x + rand::thread_rng().gen::<f32>()) / (image_width as f32);
+                let v: f32 = (y + rand::thread_rng().gen::<f32>()) / (image_height as f32);
+                if let Some(ray) = camera.ray(u, v) {
+                    color += ray_color(&ray, scene, max_bounces, RaySource::Camera);
+                }
             }
             // Because eyes can't prercive as many dark colors the values need to be gamma corrected.
             // Without the gamma correction things are a lot darker because a lot more bits are allocated to storing darker colors.
@@ -137,27 +174,125 @@ fn run(
     pixels
 }
 
-fn ray_color(ray: &Ray, hittable: &Arc<dyn Hittable>, background_color: Color, bounces_left: usize) -> Color {
+// Because of floating point rounding a ray might end up inside the sphere instead of on it which causes the ray to hit the sphere again.
+// To fix this hits very near 0 are ignored.
+const EPSILON: f32 = 0.001;
+
+// How the ray was chosen at the point it left from. Light that the ray finds and that could have also been sampled
+// directly at that point is weighted against the directly sampled light so it isn't counted twice.
+#[derive(Debug, Clone, Copy)]
+enum RaySource {
+    Camera,
+    // Chosen from a delta distribution like a mirror. Sampling lights directly can never find the same light.
+    Specular,
+    Scattered { point: Pt3, pdf: f32 }
+}
+
+// Weight of a sample chosen with the first density when another technique with the second density could have chosen it
+// too (Veach's power heuristic). The weights of both techniques add up to 1 so together they count the light once,
+// and each one is trusted more where it's the better one.
+fn power_heuristic(pdf: f32, other_pdf: f32) -> f32 {
+    if other_pdf <= 0.0 {
+        return 1.0;
+    }
+    let (a, b) = (pdf * pdf, other_pdf * other_pdf);
+    a / (a + b)
+}
+
+fn ray_color(ray: &Ray, scene: &Scene, bounces_left: usize, source: RaySource) -> Color {
     if bounces_left <= 0 {
         return Color::all(0.0);
     }
 
-    // Because of floating point rounding a ray might end up inside the sphere instead of on it which causes the ray to hit the sphere again.
-    // To fix this hits very near 0 are ignored.
-    const EPSILON: f32 = 0.001;
-    match &hittable.hit(ray, EPSILON, f32::INFINITY) {
-        None => background_color,
-        Some(record @ HitRecord { point, texture_coord, material, .. }) => {
-            let emmited = material.color_emmited(*texture_coord, *point);
+    let hit = scene.objects.hit(ray, EPSILON, f32::INFINITY);
+    // The fog only matters up to the surface. Scattering in it looks the same as hitting a medium.
+    let t_surface = hit.as_ref().map_or(f32::INFINITY, |hit| hit.t);
+    let hit = match &scene.fog {
+        Some(fog) => fog.hit(ray, EPSILON, t_surface).or(hit),
+        None => hit
+    };
+
+    match &hit {
+        None => match (&scene.camera_environment, source) {
+            (Some(environment), RaySource::Camera) => environment.color(ray.direction),
+            (_, RaySource::Scattered { pdf, .. }) =>
+                power_heuristic(pdf, scene.environment.pdf(ray.direction)) * scene.environment.color(ray.direction),
+            _ => scene.environment.color(ray.direction)
+        },
+        Some(record @ HitRecord { material, .. }) => {
+            let emmited = material.color_emmited(record);
+            // Area lights could have been sampled directly from where the ray left.
+            let emmited = match (&record.light, source) {
+                (Some(light), RaySource::Scattered { point, pdf }) if !emmited.is_near_zero() => {
+                    let light_pdf = scene.lights.probability(point, light) * light.pdf(point, ray.direction);
+                    power_heuristic(pdf, light_pdf) * emmited
+                },
+                _ => emmited
+            };
             match material.scatter(ray, &record) {
                 None => emmited,
-                Some(ScatterRecord { ray, attenuation }) => 
-                    emmited + attenuation * ray_color(&ray, hittable, background_color, bounces_left - 1)
+                Some(ScatterRecord { ray: scattered, attenuation, pdf }) => {
+                    // The scattered ray continues the cone of the incoming ray as if every surface was flat.
+                    let scattered = Ray { cone_width: record.cone_width, spread: ray.spread, ..scattered };
+                    let source = match pdf {
+                        Some(pdf) => RaySource::Scattered { point: record.point, pdf },
+                        None => RaySource::Specular
+                    };
+                    emmited + direct_light(ray, record, scene) + attenuation * ray_color(&scattered, scene, bounces_left - 1, source)
+                }
             }
         }
     }
 }
 
+// Fraction of the light coming from the direction that isn't blocked before the distance.
+fn visibility(scene: &Scene, point: Pt3, direction: Vec3, distance: f32) -> f32 {
+    // Media between the point and the light let part of the light through.
+    let shadow_ray = Ray::new(point, direction);
+    let mut transmittance = scene.objects.transmittance(&shadow_ray, EPSILON, distance - EPSILON);
+    if let Some(fog) = &scene.fog {
+        transmittance *= fog.transmittance(&shadow_ray, EPSILON, distance - EPSILON);
+    }
+    transmittance
+}
+
+// Small or far away lights are rarely found by scattered rays so instead shadow rays are sent towards them.
+fn direct_light(ray: &Ray, record: &HitRecord, scene: &Scene) -> Color {
+    sample_light(ray, record, scene) + sample_environment(ray, record, scene)
+}
+
+// A shadow ray is sent towards one of the lights to check if it's visible. Dividing by the probability of choosing
+// the light makes the result the same on average as sampling all of them.
+fn sample_light(ray: &Ray, record: &HitRecord, scene: &Scene) -> Color {
+    let Some((light, probability)) = scene.lights.sample(record.point) else { return Color::all(0.0) };
+    let Some(sample) = light.sample(record.point) else { return Color::all(0.0) };
+    let reflected = record.material.evaluate(ray, record, sample.direction);
+    if reflected.is_near_zero() {
+        return Color::all(0.0);
+    }
+    // Only area lights can also be found by scattered rays, the rest are counted fully here.
+    let light_pdf = light.pdf(record.point, sample.direction);
+    let weight = if light_pdf > 0.0 {
+        power_heuristic(probability * light_pdf, record.material.pdf(ray, record, sample.direction))
+    } else {
+        1.0
+    };
+    reflected * sample.radiance * (weight * visibility(scene, record.point, sample.direction, sample.distance) / probability)
+}
+
+// Bright parts of the environment like the sun or the windows of an HDRI are sampled directly. Scattered rays that
+// miss everything can find the same light so both are weighted.
+fn sample_environment(ray: &Ray, record: &HitRecord, scene: &Scene) -> Color {
+    let Some((direction, pdf)) = scene.environment.sample() else { return Color::all(0.0) };
+    let reflected = record.material.evaluate(ray, record, direction);
+    if pdf <= 0.0 || reflected.is_near_zero() {
+        return Color::all(0.0);
+    }
+    let weight = power_heuristic(pdf, record.material.pdf(ray, record, direction));
+    let visible = visibility(scene, record.point, direction, f32::INFINITY);
+    reflected * scene.environment.color(direction) * (weight * visible / pdf)
+}
+
 fn output_pixels(file: &mut BufWriter<File>, pixels: &[u8]) {
     for i in (0..pixels.len()).step_by(3) {
         let (r, g, b) = (pixels[i], pixels[i + 1], pixels[i + 2]);
@@ -0,0 +1,191 @@
+use rand::Rng;
+
+use crate::vec3::Vec3;
+
+const POINT_COUNT: usize = 256;
+
+// Gradient noise evaluated on a grid of simplices (tetrahedra in 3D) instead of cubes. A simplex has fewer corners
+// than a cube (n + 1 instead of 2^n) which makes the noise cheaper especially in higher dimensions.
+// Based on Stefan Gustavson's "Simplex noise demystified".
+pub struct Simplex {
+    // Doubled so indices offset by up to POINT_COUNT don't need to wrap.
+    permutations: [usize; 2 * POINT_COUNT]
+}
+
+const GRADIENTS_3D: [[f32; 3]; 12] = [
+    [1.0, 1.0, 0.0], [-1.0, 1.0, 0.0], [1.0, -1.0, 0.0], [-1.0, -1.0, 0.0],
+    [1.0, 0.0, 1.0], [-1.0, 0.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 0.0, -1.0],
+    [0.0, 1.0, 1.0], [0.0, -1.0, 1.0], [0.0, 1.0, -1.0], [0.0, -1.0, -1.0]
+];
+
+impl Simplex {
+    pub fn new() -> Self {
+        let mut shuffled = [0; POINT_COUNT];
+        for (i, item) in shuffled.iter_mut().enumerate() {
+            *item = i;
+        }
+        for i in (1..shuffled.len()).rev() {
+            let target = rand::thread_rng().gen_range(0..=i);
+            shuffled.swap(i, target);
+        }
+        let mut permutations = [0; 2 * POINT_COUNT];
+        for (i, item) in permutations.iter_mut().enumerate() {
+            *item = shuffled[i % POINT_COUNT];
+        }
+        Self { permutations }
+    }
+
+    fn wrap(n: i32) -> usize {
+        (n & (POINT_COUNT as i32 - 1)) as usize
+    }
+
+    // Gradient from all the combinations of a single 0 and three ±1.
+    fn gradient_4d(hash: usize) -> [f32; 4] {
+        let zero_position = (hash / 8) % 4;
+        let signs = hash % 8;
+        let mut gradient = [0.0; 4];
+        let mut sign_bit = 0;
+        for (i, item) in gradient.iter_mut().enumerate() {
+            if i != zero_position {
+                *item = if signs & (1 << sign_bit) != 0 { -1.0 } else { 1.0 };
+                sign_bit += 1;
+            }
+        }
+        gradient
+    }
+
+    // Returns values in range <-1, 1>.
+    pub fn noise_3d(&self, p: Vec3) -> f32 {
+        // Skewing the space turns the simplices into cubes which makes it easy to find the cell.
+        const SKEW: f32 = 1.0 / 3.0;
+        const UNSKEW: f32 = 1.0 / 6.0;
+        let s = (p.x + p.y + p.z) * SKEW;
+        let (i, j, k) = ((p.x + s).floor(), (p.y + s).floor(), (p.z + s).floor());
+        let t = (i + j + k) * UNSKEW;
+        let x0 = p - Vec3::new(i - t, j - t, k - t);
+
+        // Each cube is split into 6 simplices. Which one contains the point depends on the order of the coordinates.
+        let (o1, o2) = if x0.x >= x0.y {
+            if x0.y >= x0.z { ([1, 0, 0], [1, 1, 0]) }
+            else if x0.x >= x0.z { ([1, 0, 0], [1, 0, 1]) }
+            else { ([0, 0, 1], [1, 0, 1]) }
+        } else if x0.y < x0.z { ([0, 0, 1], [0, 1, 1]) }
+        else if x0.x < x0.z { ([0, 1, 0], [0, 1, 1]) }
+        else { ([0, 1, 0], [1, 1, 0]) };
+
+        let corners = [[0, 0, 0], o1, o2, [1, 1, 1]];
+        let (ii, jj, kk) = (i as i32, j as i32, k as i32);
+        let mut accumulated = 0.0;
+        for (n, corner) in corners.iter().enumerate() {
+            let offset = x0 - Vec3::new(corner[0] as f32, corner[1] as f32, corner[2] as f32) + Vec3::all(n as f32 * UNSKEW);
+            let falloff = 0.6 - offset.length_squared();
+            if falloff <= 0.0 {
+                continue;
+            }
+            let p = &self.permutations;
+            let hash = p[Self::wrap(ii + corner[0]) + p[Self::wrap(jj + corner[1]) + p[Self::wrap(kk + corner[2])]]] % 12;
+            let g = GRADIENTS_3D[hash];
+            accumulated += falloff.powi(4) * (g[0] * offset.x + g[1] * offset.y + g[2] * offset.z);
+        }
+        // Scales the result to <-1, 1>.
+        32.0 * accumulated
+    }
+
+    // Returns values in range <-1, 1>. The fourth coordinate is useful for animating 3D noise or getting independent slices of it.
+    pub fn noise_4d(&self, p: Vec3, w: f32) -> f32 {
+        let sqrt_5 = 5.0f32.sqrt();
+        let skew = (sqrt_5 - 1.0) / 4.0;
+        let unskew = (5.0 - sqrt_5) / 20.0;
+        let coords = [p.x, p.y, p.z, w];
+        let s = coords.iter().sum::<f32>() * skew;
+        let cell = coords.map(|c| (c + s).floor());
+        let t = cell.iter().sum::<f32>() * unskew;
+        let mut x0 = [0.0; 4];
+        for a in 0..4 {
+            x0[a] = coords[a] - (cell[a] - t);
+        }
+
+        // The simplex is found by ranking the coordinates by magnitude. The largest coordinate
+        // is the first to step to the next cell, then the second largest and so on.
+        let mut rank = [0; 4];
+        for a in 0..4 {
+            for b in (a + 1)..4 {
+                if x0[a] > x0[b] { rank[a] += 1; } else { rank[b] += 1; }
+            }
+        }
+
+        let mut accumulated = 0.0;
+        for n in 0..5 {
+            // Corner n steps in every axis with a rank of at least 4 - n.
+            let corner = rank.map(|r| if r + n >= 4 { 1 } else { 0 });
+            let mut offset = [0.0; 4];
+            let mut length_squared = 0.0;
+            for a in 0..4 {
+                offset[a] = x0[a] - corner[a] as f32 + n as f32 * unskew;
+                length_squared += offset[a] * offset[a];
+            }
+            let falloff = 0.6 - length_squared;
+            if falloff <= 0.0 {
+                continue;
+            }
+            let p = &self.permutations;
+            let hash = p[Self::wrap(cell[0] as i32 + corner[0])
+                + p[Self::wrap(cell[1] as i32 + corner[1])
+                + p[Self::wrap(cell[2] as i32 + corner[2])
+                + p[Self::wrap(cell[3] as i32 + corner[3])]]]] % 32;
+            let g = Self::gradient_4d(hash);
+            accumulated += falloff.powi(4) * (g[0] * offset[0] + g[1] * offset[1] + g[2] * offset[2] + g[3] * offset[3]);
+        }
+        // Scales the result to <-1, 1>.
+        27.0 * accumulated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_point() -> Vec3 {
+        let mut rng = rand::thread_rng();
+        Vec3::new(rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0), rng.gen_range(-50.0..50.0))
+    }
+
+    #[test]
+    fn output_range() {
+        let simplex = Simplex::new();
+        let (mut min, mut max) = (f32::INFINITY, f32::NEG_INFINITY);
+        for _ in 0..20_000 {
+            let p = random_point();
+            for value in [simplex.noise_3d(p), simplex.noise_4d(p, p.x - p.y)] {
+                assert!((-1.0..=1.0).contains(&value));
+                min = min.min(value);
+                max = max.max(value);
+            }
+        }
+        // Most of the range is used.
+        assert!(min < -0.5 && max > 0.5);
+    }
+
+    #[test]
+    fn zero_at_vertices() {
+        let simplex = Simplex::new();
+        for i in -3..3 {
+            let (j, k, l) = (2 * i + 1, -i, 3 - i);
+            // Unskewing the integer coordinates gives the vertices of the simplices.
+            let t = (i + j + k) as f32 / 6.0;
+            assert!(simplex.noise_3d(Vec3::new(i as f32 - t, j as f32 - t, k as f32 - t)).abs() < 1e-5);
+            let t = (i + j + k + l) as f32 * (5.0 - 5.0f32.sqrt()) / 20.0;
+            assert!(simplex.noise_4d(Vec3::new(i as f32 - t, j as f32 - t, k as f32 - t), l as f32 - t).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn deterministic() {
+        let simplex = Simplex::new();
+        for _ in 0..1000 {
+            let p = random_point();
+            assert_eq!(simplex.noise_3d(p), simplex.noise_3d(p));
+            assert_eq!(simplex.noise_4d(p, 0.5), simplex.noise_4d(p, 0.5));
+        }
+    }
+}
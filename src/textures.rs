@@ -11,4 +11,43 @@ mod noise_texture;
 pub use noise_texture::*;
 
 mod image_texture;
-pub use image_texture::*;
\ No newline at end of file
+pub use image_texture::*;
+
+mod alpha_channel;
+pub use alpha_channel::*;
+
+mod marble_texture;
+pub use marble_texture::*;
+
+mod wood_texture;
+pub use wood_texture::*;
+
+mod color_ramp;
+pub use color_ramp::*;
+
+mod worley_texture;
+pub use worley_texture::*;
+
+mod simplex_texture;
+pub use simplex_texture::*;
+
+mod texture_cache;
+pub use texture_cache::*;
+
+mod math_textures;
+pub use math_textures::*;
+
+mod color_textures;
+pub use color_textures::*;
+
+mod transform_textures;
+pub use transform_textures::*;
+
+mod uv_checker_texture;
+pub use uv_checker_texture::*;
+
+mod grid_texture;
+pub use grid_texture::*;
+
+mod triplanar_texture;
+pub use triplanar_texture::*;
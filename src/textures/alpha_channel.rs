@@ -0,0 +1,22 @@
+use std::sync::Arc;
+
+use crate::{vec2::Vec2, vec3::{Color, Pt3}};
+
+use super::{Texture, ImageTexture};
+
+// Exposes the alpha channel of an image as a grayscale texture so it can be used as a mask.
+pub struct AlphaChannel {
+    pub image: Arc<ImageTexture>
+}
+
+impl AlphaChannel {
+    pub fn new(image: Arc<ImageTexture>) -> Self {
+        Self { image }
+    }
+}
+
+impl Texture for AlphaChannel {
+    fn color(&self, uv: Vec2, _: Pt3) -> Color {
+        Color::all(self.image.alpha(uv))
+    }
+}
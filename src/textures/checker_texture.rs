@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use crate::{vec2::Vec2, vec3::Vec3};
+use crate::{vec2::Vec2, vec3::Vec3, hittable_objects::HitRecord};
 
 use super::Texture;
 
@@ -13,15 +13,31 @@ impl CheckerTexture {
     pub fn new(odd: Arc<dyn Texture>, even: Arc<dyn Texture>) -> Self {
         Self{ odd, even }
     }
+
+    fn is_odd(hit_point: Vec3) -> bool {
+        // Using sin becuase it changes sign cyclically.
+        (f32::sin(10.0 * hit_point.x) * f32::sin(10.0 * hit_point.y) * f32::sin(10.0 * hit_point.z)) > 0.0
+    }
 }
 
 impl Texture for CheckerTexture {
     fn color(&self, uv: Vec2, hit_point: Vec3) -> Vec3 {
-        // Using sin becuase it changes sign cyclically.
-        if (f32::sin(10.0 * hit_point.x) * f32::sin(10.0 * hit_point.y) * f32::sin(10.0 * hit_point.z)) > 0.0 {
-            self.odd.color(uv, hit_point)
+        self.color_filtered(uv, hit_point, 0.0)
+    }
+
+    fn color_filtered(&self, uv: Vec2, hit_point: Vec3, footprint: f32) -> Vec3 {
+        if Self::is_odd(hit_point) {
+            self.odd.color_filtered(uv, hit_point, footprint)
+        } else {
+            self.even.color_filtered(uv, hit_point, footprint)
+        }
+    }
+
+    fn color_at(&self, hit_record: &HitRecord) -> Vec3 {
+        if Self::is_odd(hit_record.point) {
+            self.odd.color_at(hit_record)
         } else {
-            self.even.color(uv, hit_point)
+            self.even.color_at(hit_record)
         }
     }
 }
\ No newline at end of file
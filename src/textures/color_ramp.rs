@@ -0,0 +1,36 @@
+use crate::vec3::Color;
+
+// Maps values in range <0, 1> to colors by linearly interpolating between stops.
+#[derive(Debug, Clone)]
+pub struct ColorRamp {
+    // Sorted by position.
+    stops: Vec<(f32, Color)>
+}
+
+impl ColorRamp {
+    pub fn new(mut stops: Vec<(f32, Color)>) -> Self {
+        assert!(!stops.is_empty(), "color ramp needs at least one stop");
+        stops.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        Self { stops }
+    }
+
+    pub fn grayscale() -> Self {
+        Self::new(vec![(0.0, Color::all(0.0)), (1.0, Color::all(1.0))])
+    }
+
+    pub fn color(&self, t: f32) -> Color {
+        // Values outside of the stops get the color of the closest stop.
+        let first = self.stops[0];
+        if t <= first.0 {
+            return first.1;
+        }
+        for pair in self.stops.windows(2) {
+            let ((start, start_color), (end, end_color)) = (pair[0], pair[1]);
+            if t <= end {
+                let s = if end > start { (t - start) / (end - start) } else { 1.0 };
+                return (1.0 - s) * start_color + s * end_color;
+            }
+        }
+        self.stops[self.stops.len() - 1].1
+    }
+}
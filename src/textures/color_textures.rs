@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use crate::{vec2::Vec2, vec3::{Color, Pt3}, hittable_objects::HitRecord};
+
+use super::{Texture, ColorRamp};
+
+// Maps the value of the input texture to a color. Useful for coloring noise.
+pub struct RampTexture {
+    pub input: Arc<dyn Texture>,
+    pub ramp: ColorRamp
+}
+
+impl RampTexture {
+    pub fn new(input: Arc<dyn Texture>, ramp: ColorRamp) -> Self {
+        Self { input, ramp }
+    }
+
+    fn map(&self, color: Color) -> Color {
+        self.ramp.color((color.x + color.y + color.z) / 3.0)
+    }
+}
+
+impl Texture for RampTexture {
+    fn color(&self, uv: Vec2, hit_point: Pt3) -> Color {
+        self.color_filtered(uv, hit_point, 0.0)
+    }
+
+    fn color_filtered(&self, uv: Vec2, hit_point: Pt3, footprint: f32) -> Color {
+        self.map(self.input.color_filtered(uv, hit_point, footprint))
+    }
+
+    fn color_at(&self, hit_record: &HitRecord) -> Color {
+        self.map(self.input.color_at(hit_record))
+    }
+}
+
+pub struct HsvAdjust {
+    pub texture: Arc<dyn Texture>,
+    // In turns so 1 is a full rotation around the color wheel.
+    pub hue_shift: f32,
+    pub saturation: f32,
+    pub value: f32
+}
+
+impl HsvAdjust {
+    pub fn new(texture: Arc<dyn Texture>, hue_shift: f32, saturation: f32, value: f32) -> Self {
+        Self { texture, hue_shift, saturation, value }
+    }
+
+    // Hue is in turns and saturation and value in range <0, 1>.
+    fn rgb_to_hsv(color: Color) -> (f32, f32, f32) {
+        let max = color.x.max(color.y).max(color.z);
+        let min = color.x.min(color.y).min(color.z);
+        let chroma = max - min;
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == color.x {
+            ((color.y - color.z) / chroma).rem_euclid(6.0)
+        } else if max == color.y {
+            (color.z - color.x) / chroma + 2.0
+        } else {
+            (color.x - color.y) / chroma + 4.0
+        };
+        let saturation = if max > 0.0 { chroma / max } else { 0.0 };
+        (hue / 6.0, saturation, max)
+    }
+
+    fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> Color {
+        let chroma = value * saturation;
+        let h = hue.rem_euclid(1.0) * 6.0;
+        let x = chroma * (1.0 - ((h % 2.0) - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x)
+        };
+        Color::new(r, g, b) + Color::all(value - chroma)
+    }
+
+    fn adjust(&self, color: Color) -> Color {
+        let (hue, saturation, value) = Self::rgb_to_hsv(color);
+        Self::hsv_to_rgb(hue + self.hue_shift, (saturation * self.saturation).clamp(0.0, 1.0), value * self.value)
+    }
+}
+
+impl Texture for HsvAdjust {
+    fn color(&self, uv: Vec2, hit_point: Pt3) -> Color {
+        self.color_filtered(uv, hit_point, 0.0)
+    }
+
+    fn color_filtered(&self, uv: Vec2, hit_point: Pt3, footprint: f32) -> Color {
+        self.adjust(self.texture.color_filtered(uv, hit_point, footprint))
+    }
+
+    fn color_at(&self, hit_record: &HitRecord) -> Color {
+        self.adjust(self.texture.color_at(hit_record))
+    }
+}
@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use crate::{vec2::Vec2, vec3::{Color, Pt3}, hittable_objects::HitRecord};
+
+use super::Texture;
+
+// Lines of a grid in texture coordinates. Useful for checking how texture coordinates are laid out on a surface.
+pub struct GridTexture {
+    pub line: Arc<dyn Texture>,
+    pub fill: Arc<dyn Texture>,
+    // Number of cells along u and v.
+    pub cells: Vec2,
+    // Width of the lines as a fraction of the cell size.
+    pub line_width: f32
+}
+
+impl GridTexture {
+    pub fn new(line: Arc<dyn Texture>, fill: Arc<dyn Texture>, cells: Vec2, line_width: f32) -> Self {
+        Self { line, fill, cells, line_width }
+    }
+
+    fn is_line(&self, uv: Vec2) -> bool {
+        // Distance to the closest line as a fraction of the cell size.
+        let distance = |t: f32| {
+            let f = t.rem_euclid(1.0);
+            f32::min(f, 1.0 - f)
+        };
+        let half_width = self.line_width / 2.0;
+        distance(uv.x * self.cells.x) < half_width || distance(uv.y * self.cells.y) < half_width
+    }
+}
+
+impl Texture for GridTexture {
+    fn color(&self, uv: Vec2, hit_point: Pt3) -> Color {
+        self.color_filtered(uv, hit_point, 0.0)
+    }
+
+    fn color_filtered(&self, uv: Vec2, hit_point: Pt3, footprint: f32) -> Color {
+        if self.is_line(uv) {
+            self.line.color_filtered(uv, hit_point, footprint)
+        } else {
+            self.fill.color_filtered(uv, hit_point, footprint)
+        }
+    }
+
+    fn color_at(&self, hit_record: &HitRecord) -> Color {
+        if self.is_line(hit_record.texture_coord) {
+            self.line.color_at(hit_record)
+        } else {
+            self.fill.color_at(hit_record)
+        }
+    }
+}
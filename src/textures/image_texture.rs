@@ -1,30 +1,353 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use image::{DynamicImage, GenericImageView};
+use image::{DynamicImage, ImageError};
 
 use crate::{vec3::Vec3, vec2::Vec2};
 
 use super::Texture;
 
+// What happens to texture coordinates outside of range <0, 1>.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WrapMode {
+    Repeat,
+    // Repeats with every other copy flipped so there are no seams.
+    Mirror,
+    Clamp
+}
+
+impl WrapMode {
+    fn wrap(&self, i: i64, size: usize) -> usize {
+        let size = size as i64;
+        let wrapped = match self {
+            WrapMode::Repeat => i.rem_euclid(size),
+            WrapMode::Mirror => {
+                let m = i.rem_euclid(2 * size);
+                if m >= size { 2 * size - 1 - m } else { m }
+            },
+            WrapMode::Clamp => i.clamp(0, size - 1)
+        };
+        wrapped as usize
+    }
+}
+
+// How the values stored in the image map to the values used for rendering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    // Most 8 and 16 bit color images are stored in sRGB which allocates more values to darker colors because eyes are more
+    // sensitive to them. The values have to be converted to linear light, otherwise they come out too bright and washed out.
+    Srgb,
+    // Data like normals, roughness or masks is stored as is.
+    Linear
+}
+
+impl ColorSpace {
+    fn to_linear(self, value: f32) -> f32 {
+        match self {
+            ColorSpace::Srgb => if value <= 0.04045 {
+                value / 12.92
+            } else {
+                ((value + 0.055) / 1.055).powf(2.4)
+            },
+            ColorSpace::Linear => value
+        }
+    }
+}
+
+// Applied to the texture coordinates before sampling. Scaling, then rotating around the origin and then offsetting.
+#[derive(Debug, Clone, Copy)]
+pub struct UvTransform {
+    pub scale: Vec2,
+    pub offset: Vec2,
+    // In radians.
+    pub rotation: f32
+}
+
+impl UvTransform {
+    pub fn identity() -> Self {
+        Self { scale: Vec2::all(1.0), offset: Vec2::all(0.0), rotation: 0.0 }
+    }
+
+    pub fn apply(&self, uv: Vec2) -> Vec2 {
+        let (sin, cos) = self.rotation.sin_cos();
+        let (x, y) = (uv.x * self.scale.x, uv.y * self.scale.y);
+        Vec2::new(cos * x - sin * y + self.offset.x, sin * x + cos * y + self.offset.y)
+    }
+}
+
+// Single level of the mip map. Pixels are stored as rgba going from the top left.
+struct MipLevel {
+    width: usize,
+    height: usize,
+    pixels: Vec<[f32; 4]>
+}
+
+impl MipLevel {
+    fn pixel(&self, x: usize, y: usize) -> [f32; 4] {
+        self.pixels[y * self.width + x]
+    }
+
+    // Each pixel of the next level is the average of 2x2 pixels of this one.
+    fn downsampled(&self) -> MipLevel {
+        let (width, height) = (usize::max(self.width / 2, 1), usize::max(self.height / 2, 1));
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = [0.0; 4];
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    // With odd sizes the last pixel is used twice.
+                    let pixel = self.pixel(usize::min(2 * x + dx, self.width - 1), usize::min(2 * y + dy, self.height - 1));
+                    for c in 0..4 {
+                        sum[c] += pixel[c] / 4.0;
+                    }
+                }
+                pixels.push(sum);
+            }
+        }
+        MipLevel { width, height, pixels }
+    }
+}
+
+// Mip map levels each one half the size of the previous one. Far away surfaces cover many pixels of the image in
+// a single sample so without prefiltering the texture would alias.
+// Shared between all the textures created from the same image.
+pub struct MipMap {
+    levels: Vec<MipLevel>
+}
+
+impl MipMap {
+    pub fn from_image(image: DynamicImage, color_space: ColorSpace) -> MipMap {
+        // Floating point images like .hdr and .exr already store linear values that can go above 1.
+        let color_space = match image {
+            DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_) => ColorSpace::Linear,
+            _ => color_space
+        };
+        // Converting to floats directly so 16 bit and floating point images don't lose precision.
+        let image = image.to_rgba32f();
+        let pixels = image.pixels()
+            .map(|pixel| {
+                let [r, g, b, a] = pixel.0;
+                // Alpha is always linear.
+                [color_space.to_linear(r), color_space.to_linear(g), color_space.to_linear(b), a]
+            })
+            .collect();
+        let mut levels = vec![MipLevel { width: image.width() as usize, height: image.height() as usize, pixels }];
+        while let Some(last) = levels.last() {
+            if last.width == 1 && last.height == 1 {
+                break;
+            }
+            levels.push(last.downsampled());
+        }
+        MipMap { levels }
+    }
+
+    fn bilinear(level: &MipLevel, uv: Vec2, wrap_mode: WrapMode) -> [f32; 4] {
+        // Pixel centers are at half integer coordinates. The image is stored from the top so v is flipped.
+        let x = uv.x * level.width as f32 - 0.5;
+        let y = (1.0 - uv.y) * level.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let mut result = [0.0; 4];
+        for (dx, dy, weight) in [(0, 0, (1.0 - tx) * (1.0 - ty)), (1, 0, tx * (1.0 - ty)), (0, 1, (1.0 - tx) * ty), (1, 1, tx * ty)] {
+            let pixel = level.pixel(wrap_mode.wrap(x0 + dx, level.width), wrap_mode.wrap(y0 + dy, level.height));
+            for c in 0..4 {
+                result[c] += weight * pixel[c];
+            }
+        }
+        result
+    }
+
+    // Level where a pixel is about the size of the footprint. Fractional between two levels.
+    fn level(&self, footprint: f32) -> f32 {
+        let base = &self.levels[0];
+        let pixels_covered = footprint * usize::max(base.width, base.height) as f32;
+        let level = if pixels_covered > 1.0 { pixels_covered.log2() } else { 0.0 };
+        f32::min(level, (self.levels.len() - 1) as f32)
+    }
+
+    // Trilinear filtering. Chooses the levels where a pixel is about the size of the footprint and interpolates between them.
+    fn sample(&self, uv: Vec2, footprint: f32, wrap_mode: WrapMode) -> [f32; 4] {
+        let level = self.level(footprint);
+        let lower = level.floor() as usize;
+        let upper = usize::min(lower + 1, self.levels.len() - 1);
+        let t = level - lower as f32;
+        let a = Self::bilinear(&self.levels[lower], uv, wrap_mode);
+        let b = Self::bilinear(&self.levels[upper], uv, wrap_mode);
+        [0, 1, 2, 3].map(|c| (1.0 - t) * a[c] + t * b[c])
+    }
+}
+
+#[derive(Debug)]
+pub struct TextureLoadError {
+    pub path: PathBuf,
+    pub cause: ImageError
+}
+
+impl std::fmt::Display for TextureLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failed to load texture {}: {}", self.path.display(), self.cause)
+    }
+}
+
+impl std::error::Error for TextureLoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.cause)
+    }
+}
+
 pub struct ImageTexture {
-    image: DynamicImage
+    pub mip_map: Arc<MipMap>,
+    pub wrap_mode: WrapMode,
+    pub transform: UvTransform
 }
 
 impl ImageTexture {
-    pub fn from_file(path: &Path) -> ImageTexture {
+    pub fn new(mip_map: Arc<MipMap>) -> ImageTexture {
+        ImageTexture { mip_map, wrap_mode: WrapMode::Clamp, transform: UvTransform::identity() }
+    }
+
+    pub fn from_file(path: &Path) -> Result<ImageTexture, TextureLoadError> {
+        Self::from_file_in_color_space(path, ColorSpace::Srgb)
+    }
+
+    pub fn from_file_in_color_space(path: &Path, color_space: ColorSpace) -> Result<ImageTexture, TextureLoadError> {
         match image::open(path) {
-            Ok(image) => ImageTexture{ image },
-            Err(_) => panic!("failed to open texture"),
+            Ok(image) => Ok(Self::from_image(image, color_space)),
+            Err(cause) => Err(TextureLoadError { path: path.to_path_buf(), cause }),
         }
     }
+
+    pub fn from_image(image: DynamicImage, color_space: ColorSpace) -> ImageTexture {
+        Self::new(Arc::new(MipMap::from_image(image, color_space)))
+    }
+
+    pub fn with_wrap_mode(self, wrap_mode: WrapMode) -> Self {
+        Self { wrap_mode, ..self }
+    }
+
+    pub fn with_transform(self, transform: UvTransform) -> Self {
+        Self { transform, ..self }
+    }
+
+    // Images without an alpha channel are fully opaque.
+    pub fn alpha(&self, uv: Vec2) -> f32 {
+        self.mip_map.sample(self.transform.apply(uv), 0.0, self.wrap_mode)[3]
+    }
 }
 
 impl Texture for ImageTexture {
-    fn color(&self, mut uv: Vec2, _: Vec3) -> Vec3 {
-        uv.apply(|n| n.clamp(0.0, 0.999));
-        let x = (uv.x * self.image.width() as f32) as u32;
-        let y = ((0.999 - uv.y) * self.image.height() as f32) as u32;
-        let pixel = self.image.get_pixel(x, y).0;
-        Vec3::new(pixel[0] as f32 / 255.0, pixel[1] as f32 / 255.0, pixel[2] as f32 / 255.0)
-    }
-}
\ No newline at end of file
+    fn color(&self, uv: Vec2, hit_point: Vec3) -> Vec3 {
+        self.color_filtered(uv, hit_point, 0.0)
+    }
+
+    fn color_filtered(&self, uv: Vec2, _: Vec3, footprint: f32) -> Vec3 {
+        // The transform changes how much of the texture the footprint covers.
+        let scale = f32::max(self.transform.scale.x.abs(), self.transform.scale.y.abs());
+        let pixel = self.mip_map.sample(self.transform.apply(uv), footprint * scale, self.wrap_mode);
+        Vec3::new(pixel[0], pixel[1], pixel[2])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Rgb, Rgba};
+
+    // The standard sRGB encoding, the inverse of to_linear.
+    fn to_srgb(value: f32) -> f32 {
+        if value <= 0.0031308 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 }
+    }
+
+    #[test]
+    fn srgb_to_linear() {
+        assert_eq!(ColorSpace::Srgb.to_linear(0.0), 0.0);
+        assert!((ColorSpace::Srgb.to_linear(1.0) - 1.0).abs() < 1e-6);
+        assert!((ColorSpace::Srgb.to_linear(0.5) - 0.214041).abs() < 1e-5);
+        // Both parts of the curve meet at the threshold.
+        assert!((ColorSpace::Srgb.to_linear(0.04045) - ColorSpace::Srgb.to_linear(0.040451)).abs() < 1e-6);
+        assert_eq!(ColorSpace::Linear.to_linear(0.5), 0.5);
+
+        // Every 8 bit value comes back after converting to linear and back.
+        for i in 0..=255 {
+            let value = i as f32 / 255.0;
+            let linear = ColorSpace::Srgb.to_linear(value);
+            assert!(linear <= value + 1e-6);
+            assert!(((to_srgb(linear) * 255.0).round() - i as f32).abs() < 0.5, "{} didn't round trip", i);
+        }
+    }
+
+    #[test]
+    fn decoded_images() {
+        // 8 bit images are decoded from sRGB but alpha stays linear.
+        let image = ImageBuffer::from_pixel(1, 1, Rgba([128u8, 255, 0, 128]));
+        let pixel = MipMap::from_image(DynamicImage::ImageRgba8(image), ColorSpace::Srgb).levels[0].pixel(0, 0);
+        assert!((pixel[0] - ColorSpace::Srgb.to_linear(128.0 / 255.0)).abs() < 1e-6);
+        assert!((pixel[1] - 1.0).abs() < 1e-6 && pixel[2] == 0.0);
+        assert!((pixel[3] - 128.0 / 255.0).abs() < 1e-6);
+
+        // 16 bit images keep the values between the 8 bit ones.
+        let image = ImageBuffer::from_pixel(1, 1, Rgb([32896u16, 32897, 0]));
+        let pixel = MipMap::from_image(DynamicImage::ImageRgb16(image), ColorSpace::Linear).levels[0].pixel(0, 0);
+        assert!(pixel[0] < pixel[1]);
+
+        // Floating point images are already linear even when sRGB is asked for.
+        let image = ImageBuffer::from_pixel(1, 1, Rgb([0.5f32, 2.0, 0.0]));
+        let pixel = MipMap::from_image(DynamicImage::ImageRgb32F(image), ColorSpace::Srgb).levels[0].pixel(0, 0);
+        assert_eq!(&pixel[..3], &[0.5, 2.0, 0.0]);
+    }
+
+    #[test]
+    fn wrap_modes() {
+        let wrapped = |mode: WrapMode| [-5, -4, -1, 0, 3, 4, 7, 8].map(|i| mode.wrap(i, 4));
+        assert_eq!(wrapped(WrapMode::Repeat), [3, 0, 3, 0, 3, 0, 3, 0]);
+        // Mirrored copies repeat the edge texel so there is no seam.
+        assert_eq!(wrapped(WrapMode::Mirror), [3, 3, 0, 0, 3, 3, 0, 0]);
+        assert_eq!(wrapped(WrapMode::Clamp), [0, 0, 0, 0, 3, 3, 3, 3]);
+    }
+
+    // Gray values going up by 0.25 from left to right, in the first row, and by 0.5 from top to bottom.
+    fn gradient() -> MipMap {
+        let image = ImageBuffer::from_fn(4, 2, |x, y| Rgb([0.25 * x as f32 + 0.5 * y as f32; 3]));
+        MipMap::from_image(DynamicImage::ImageRgb32F(image), ColorSpace::Linear)
+    }
+
+    #[test]
+    fn bilinear_weights() {
+        let mip_map = gradient();
+        let level = &mip_map.levels[0];
+        // At pixel centers only that pixel is used. The image is stored from the top and v goes up.
+        for x in 0..4 {
+            for y in 0..2 {
+                let uv = Vec2::new((x as f32 + 0.5) / 4.0, 1.0 - (y as f32 + 0.5) / 2.0);
+                assert_eq!(MipMap::bilinear(level, uv, WrapMode::Clamp), level.pixel(x, y));
+            }
+        }
+        // Halfway between four pixel centers they are averaged.
+        let between = MipMap::bilinear(level, Vec2::new(0.5, 0.5), WrapMode::Clamp)[0];
+        assert!((between - (0.25 + 0.5 + 0.75 + 1.0) / 4.0).abs() < 1e-6);
+        // Past the edge the other side is blended in only when repeating.
+        let edge = Vec2::new(0.0, 0.75);
+        assert!((MipMap::bilinear(level, edge, WrapMode::Clamp)[0] - 0.0).abs() < 1e-6);
+        assert!((MipMap::bilinear(level, edge, WrapMode::Repeat)[0] - 0.375).abs() < 1e-6);
+    }
+
+    #[test]
+    fn mip_level_selection() {
+        let mip_map = gradient();
+        assert_eq!(mip_map.levels.iter().map(|level| (level.width, level.height)).collect::<Vec<_>>(), vec![(4, 2), (2, 1), (1, 1)]);
+        // Up to a pixel of the widest side the full resolution is used.
+        assert_eq!(mip_map.level(0.0), 0.0);
+        assert_eq!(mip_map.level(0.25), 0.0);
+        // Each doubling of the footprint moves a level down.
+        assert!((mip_map.level(0.5) - 1.0).abs() < 1e-6);
+        assert!((mip_map.level(0.25 * 2.0f32.sqrt()) - 0.5).abs() < 1e-6);
+        // It stops at the last level.
+        assert_eq!(mip_map.level(100.0), 2.0);
+
+        // The last level is the average of the whole image.
+        let average = mip_map.sample(Vec2::new(0.3, 0.7), 100.0, WrapMode::Clamp)[0];
+        assert!((average - 0.625).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,29 @@
+use crate::{perlin::{Perlin, Turbulence}, vec2::Vec2, vec3::{Vec3, Color}};
+
+use super::Texture;
+
+pub struct MarbleTexture {
+    pub perlin: Perlin,
+    pub turbulence: Turbulence,
+    // Number of veins per unit of distance.
+    pub frequency: f32,
+    // How much the turbulence bends the veins.
+    pub distortion: f32,
+    pub base_color: Color,
+    pub vein_color: Color
+}
+
+impl MarbleTexture {
+    pub fn new(frequency: f32, base_color: Color, vein_color: Color) -> Self {
+        Self { perlin: Perlin::new(), turbulence: Turbulence::default(), frequency, distortion: 10.0, base_color, vein_color }
+    }
+}
+
+impl Texture for MarbleTexture {
+    fn color(&self, _: Vec2, hit_point: Vec3) -> Vec3 {
+        // Stripes created by a sine wave along the z axis with the phase shifted by turbulence.
+        let phase = self.frequency * hit_point.z + self.distortion * self.perlin.turbulence(hit_point, self.turbulence);
+        let t = 0.5 * (1.0 + phase.sin());
+        (1.0 - t) * self.vein_color + t * self.base_color
+    }
+}
@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use crate::{vec2::Vec2, vec3::{Color, Pt3}, hittable_objects::HitRecord};
+
+use super::Texture;
+
+// Small textures that combine other textures. They pass the footprint and the hit record to their inputs
+// so filtering and textures that need the surface still work through them.
+
+pub struct ScaleTexture {
+    pub texture: Arc<dyn Texture>,
+    pub factor: Color
+}
+
+impl ScaleTexture {
+    pub fn new(texture: Arc<dyn Texture>, factor: Color) -> Self {
+        Self { texture, factor }
+    }
+}
+
+impl Texture for ScaleTexture {
+    fn color(&self, uv: Vec2, hit_point: Pt3) -> Color {
+        self.color_filtered(uv, hit_point, 0.0)
+    }
+
+    fn color_filtered(&self, uv: Vec2, hit_point: Pt3, footprint: f32) -> Color {
+        self.factor * self.texture.color_filtered(uv, hit_point, footprint)
+    }
+
+    fn color_at(&self, hit_record: &HitRecord) -> Color {
+        self.factor * self.texture.color_at(hit_record)
+    }
+}
+
+pub struct AddTexture {
+    pub a: Arc<dyn Texture>,
+    pub b: Arc<dyn Texture>
+}
+
+impl AddTexture {
+    pub fn new(a: Arc<dyn Texture>, b: Arc<dyn Texture>) -> Self {
+        Self { a, b }
+    }
+}
+
+impl Texture for AddTexture {
+    fn color(&self, uv: Vec2, hit_point: Pt3) -> Color {
+        self.color_filtered(uv, hit_point, 0.0)
+    }
+
+    fn color_filtered(&self, uv: Vec2, hit_point: Pt3, footprint: f32) -> Color {
+        self.a.color_filtered(uv, hit_point, footprint) + self.b.color_filtered(uv, hit_point, footprint)
+    }
+
+    fn color_at(&self, hit_record: &HitRecord) -> Color {
+        self.a.color_at(hit_record) + self.b.color_at(hit_record)
+    }
+}
+
+pub struct MultiplyTexture {
+    pub a: Arc<dyn Texture>,
+    pub b: Arc<dyn Texture>
+}
+
+impl MultiplyTexture {
+    pub fn new(a: Arc<dyn Texture>, b: Arc<dyn Texture>) -> Self {
+        Self { a, b }
+    }
+}
+
+impl Texture for MultiplyTexture {
+    fn color(&self, uv: Vec2, hit_point: Pt3) -> Color {
+        self.color_filtered(uv, hit_point, 0.0)
+    }
+
+    fn color_filtered(&self, uv: Vec2, hit_point: Pt3, footprint: f32) -> Color {
+        self.a.color_filtered(uv, hit_point, footprint) * self.b.color_filtered(uv, hit_point, footprint)
+    }
+
+    fn color_at(&self, hit_record: &HitRecord) -> Color {
+        self.a.color_at(hit_record) * self.b.color_at(hit_record)
+    }
+}
+
+// Linear interpolation from a to b. The mask is interpolated per channel so colored masks are also possible.
+pub struct LerpTexture {
+    pub a: Arc<dyn Texture>,
+    pub b: Arc<dyn Texture>,
+    pub mask: Arc<dyn Texture>
+}
+
+impl LerpTexture {
+    pub fn new(a: Arc<dyn Texture>, b: Arc<dyn Texture>, mask: Arc<dyn Texture>) -> Self {
+        Self { a, b, mask }
+    }
+}
+
+impl Texture for LerpTexture {
+    fn color(&self, uv: Vec2, hit_point: Pt3) -> Color {
+        self.color_filtered(uv, hit_point, 0.0)
+    }
+
+    fn color_filtered(&self, uv: Vec2, hit_point: Pt3, footprint: f32) -> Color {
+        let t = self.mask.color_filtered(uv, hit_point, footprint);
+        (Color::all(1.0) - t) * self.a.color_filtered(uv, hit_point, footprint) + t * self.b.color_filtered(uv, hit_point, footprint)
+    }
+
+    fn color_at(&self, hit_record: &HitRecord) -> Color {
+        let t = self.mask.color_at(hit_record);
+        (Color::all(1.0) - t) * self.a.color_at(hit_record) + t * self.b.color_at(hit_record)
+    }
+}
+
+pub struct InvertTexture {
+    pub texture: Arc<dyn Texture>
+}
+
+impl InvertTexture {
+    pub fn new(texture: Arc<dyn Texture>) -> Self {
+        Self { texture }
+    }
+}
+
+impl Texture for InvertTexture {
+    fn color(&self, uv: Vec2, hit_point: Pt3) -> Color {
+        self.color_filtered(uv, hit_point, 0.0)
+    }
+
+    fn color_filtered(&self, uv: Vec2, hit_point: Pt3, footprint: f32) -> Color {
+        Color::all(1.0) - self.texture.color_filtered(uv, hit_point, footprint)
+    }
+
+    fn color_at(&self, hit_record: &HitRecord) -> Color {
+        Color::all(1.0) - self.texture.color_at(hit_record)
+    }
+}
+
+pub struct ClampTexture {
+    pub texture: Arc<dyn Texture>,
+    pub min: f32,
+    pub max: f32
+}
+
+impl ClampTexture {
+    pub fn new(texture: Arc<dyn Texture>, min: f32, max: f32) -> Self {
+        Self { texture, min, max }
+    }
+
+    fn clamp(&self, color: Color) -> Color {
+        Color::new(color.x.clamp(self.min, self.max), color.y.clamp(self.min, self.max), color.z.clamp(self.min, self.max))
+    }
+}
+
+impl Texture for ClampTexture {
+    fn color(&self, uv: Vec2, hit_point: Pt3) -> Color {
+        self.color_filtered(uv, hit_point, 0.0)
+    }
+
+    fn color_filtered(&self, uv: Vec2, hit_point: Pt3, footprint: f32) -> Color {
+        self.clamp(self.texture.color_filtered(uv, hit_point, footprint))
+    }
+
+    fn color_at(&self, hit_record: &HitRecord) -> Color {
+        self.clamp(self.texture.color_at(hit_record))
+    }
+}
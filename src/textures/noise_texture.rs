@@ -3,17 +3,20 @@ use crate::{perlin::Perlin, vec2::Vec2, vec3::Vec3};
 use super::Texture;
 
 pub struct NoiseTexture {
-    pub perlin: Perlin
+    pub perlin: Perlin,
+    // Number of noise cells per unit of distance.
+    pub frequency: f32
 }
 
 impl NoiseTexture {
-    pub fn new() -> Self {
-        Self { perlin: Perlin::new() }
+    pub fn new(frequency: f32) -> Self {
+        Self { perlin: Perlin::new(), frequency }
     }
 }
 
 impl Texture for NoiseTexture {
     fn color(&self, _: Vec2, hit_point: Vec3) -> Vec3 {
-        self.perlin.get(hit_point) * Vec3::all(1.0)
+        // Mapping from <-1, 1> to <0, 1>.
+        0.5 * (1.0 + self.perlin.noise(self.frequency * hit_point)) * Vec3::all(1.0)
     }
-}
\ No newline at end of file
+}
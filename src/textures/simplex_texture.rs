@@ -0,0 +1,34 @@
+use crate::{simplex::Simplex, vec2::Vec2, vec3::Vec3};
+
+use super::{Texture, ColorRamp};
+
+pub struct SimplexTexture {
+    pub simplex: Simplex,
+    // Number of noise cells per unit of distance.
+    pub frequency: f32,
+    // When set the 4D noise is used with this as the fourth coordinate.
+    pub w: Option<f32>,
+    pub ramp: ColorRamp
+}
+
+impl SimplexTexture {
+    pub fn new(frequency: f32, ramp: ColorRamp) -> Self {
+        Self { simplex: Simplex::new(), frequency, w: None, ramp }
+    }
+
+    pub fn new_4d(frequency: f32, w: f32, ramp: ColorRamp) -> Self {
+        Self { simplex: Simplex::new(), frequency, w: Some(w), ramp }
+    }
+}
+
+impl Texture for SimplexTexture {
+    fn color(&self, _: Vec2, hit_point: Vec3) -> Vec3 {
+        let p = self.frequency * hit_point;
+        let noise = match self.w {
+            Some(w) => self.simplex.noise_4d(p, self.frequency * w),
+            None => self.simplex.noise_3d(p)
+        };
+        // Mapping from <-1, 1> to <0, 1>.
+        self.ramp.color(0.5 * (1.0 + noise))
+    }
+}
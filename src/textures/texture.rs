@@ -1,5 +1,28 @@
-use crate::{vec3::{Pt3, Color}, vec2::Vec2};
+use crate::{vec3::{Pt3, Color}, vec2::Vec2, hittable_objects::HitRecord};
 
 pub trait Texture where Self: Send + Sync {
     fn color(&self, uv: Vec2, hit_point: Pt3) -> Color;
-}
\ No newline at end of file
+
+    // Used when a texture controls a single parameter like a weight or a mask.
+    fn value(&self, uv: Vec2, hit_point: Pt3) -> f32 {
+        let color = self.color(uv, hit_point);
+        (color.x + color.y + color.z) / 3.0
+    }
+
+    // The footprint is the width of the area the sample covers in texture coordinates.
+    // Only textures that can filter, like images, need to use it.
+    fn color_filtered(&self, uv: Vec2, hit_point: Pt3, _footprint: f32) -> Color {
+        self.color(uv, hit_point)
+    }
+
+    // Used by materials. Textures that need more information about the surface, like its normal, can override this.
+    fn color_at(&self, hit_record: &HitRecord) -> Color {
+        self.color_filtered(hit_record.texture_coord, hit_record.point, hit_record.texture_footprint())
+    }
+
+    // The same as value but for materials.
+    fn value_at(&self, hit_record: &HitRecord) -> f32 {
+        let color = self.color_at(hit_record);
+        (color.x + color.y + color.z) / 3.0
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use image::ImageError;
+
+use super::{ImageTexture, MipMap, ColorSpace, TextureLoadError};
+
+// Makes all the textures loaded from the same file share a single decoded image.
+// Each texture still has its own wrap mode and transform.
+pub struct TextureCache {
+    // The same file can be decoded differently depending on the color space.
+    images: Mutex<HashMap<(PathBuf, ColorSpace), Arc<MipMap>>>
+}
+
+impl TextureCache {
+    pub fn new() -> Self {
+        Self { images: Mutex::new(HashMap::new()) }
+    }
+
+    // The lock is only held to look up or insert a finished image so a thread that panicked can't have left the map
+    // half updated. Carrying on with it lets the other threads keep rendering.
+    fn images(&self) -> MutexGuard<'_, HashMap<(PathBuf, ColorSpace), Arc<MipMap>>> {
+        self.images.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn load(&self, path: &Path) -> Result<ImageTexture, TextureLoadError> {
+        self.load_in_color_space(path, ColorSpace::Srgb)
+    }
+
+    pub fn load_in_color_space(&self, path: &Path, color_space: ColorSpace) -> Result<ImageTexture, TextureLoadError> {
+        // Using the canonical path so different ways of writing the same path don't decode the image again.
+        let canonical_path = match path.canonicalize() {
+            Ok(canonical_path) => canonical_path,
+            Err(error) => return Err(TextureLoadError { path: path.to_path_buf(), cause: ImageError::IoError(error) })
+        };
+
+        let key = (canonical_path, color_space);
+        if let Some(mip_map) = self.images().get(&key) {
+            return Ok(ImageTexture::new(mip_map.clone()));
+        }
+
+        // Decoding without holding the lock. If two threads load the same image at once it just gets decoded twice.
+        let image = image::open(&key.0).map_err(|cause| TextureLoadError { path: path.to_path_buf(), cause })?;
+        let mip_map = Arc::new(MipMap::from_image(image, color_space));
+        self.images().insert(key, mip_map.clone());
+        Ok(ImageTexture::new(mip_map))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, RgbImage};
+
+    // A small image in its own directory so tests running at the same time don't share files.
+    fn write_image(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("texture_cache_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(directory.join("nested")).unwrap();
+        let path = directory.join("image.png");
+        DynamicImage::ImageRgb8(RgbImage::from_pixel(2, 2, image::Rgb([10, 20, 30]))).save(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn shares_decoded_images() {
+        let path = write_image("shares");
+        let cache = TextureCache::new();
+        let first = cache.load(&path).unwrap();
+        let second = cache.load(&path).unwrap();
+        assert!(Arc::ptr_eq(&first.mip_map, &second.mip_map));
+
+        // Another way of writing the same path.
+        let roundabout = path.parent().unwrap().join("nested").join("..").join(".").join("image.png");
+        assert_ne!(roundabout, path);
+        assert!(Arc::ptr_eq(&first.mip_map, &cache.load(&roundabout).unwrap().mip_map));
+
+        // Linear data is decoded separately.
+        assert!(!Arc::ptr_eq(&first.mip_map, &cache.load_in_color_space(&path, ColorSpace::Linear).unwrap().mip_map));
+        assert_eq!(cache.images().len(), 2);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn missing_file() {
+        let path = std::env::temp_dir().join("texture_cache_missing").join("nothing.png");
+        let error = TextureCache::new().load(&path).err().unwrap();
+        assert_eq!(error.path, path);
+        assert!(matches!(error.cause, ImageError::IoError(_)));
+    }
+
+    #[test]
+    fn survives_a_poisoned_lock() {
+        let path = write_image("poisoned");
+        let cache = Arc::new(TextureCache::new());
+        let poisoning = cache.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoning.images.lock().unwrap();
+            panic!("panicked while holding the lock");
+        }).join();
+        assert!(cache.images.is_poisoned());
+        assert!(cache.load(&path).is_ok());
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
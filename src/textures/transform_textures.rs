@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use crate::{vec2::Vec2, vec3::{Color, Pt3, Vec3}, hittable_objects::HitRecord};
+
+use super::{Texture, UvTransform};
+
+// Changes the texture coordinates before passing them to the texture. Can be used to tile or rotate any texture.
+pub struct UvTransformTexture {
+    pub texture: Arc<dyn Texture>,
+    pub transform: UvTransform
+}
+
+impl UvTransformTexture {
+    pub fn new(texture: Arc<dyn Texture>, transform: UvTransform) -> Self {
+        Self { texture, transform }
+    }
+
+    // How many times the texture is repeated over the original texture coordinates, which is how much larger the footprint gets.
+    fn scale(&self) -> f32 {
+        f32::max(self.transform.scale.x.abs(), self.transform.scale.y.abs())
+    }
+}
+
+impl Texture for UvTransformTexture {
+    fn color(&self, uv: Vec2, hit_point: Pt3) -> Color {
+        self.color_filtered(uv, hit_point, 0.0)
+    }
+
+    fn color_filtered(&self, uv: Vec2, hit_point: Pt3, footprint: f32) -> Color {
+        self.texture.color_filtered(self.transform.apply(uv), hit_point, footprint * self.scale())
+    }
+
+    // The tangents get shorter by the same amount so the footprint of the hit record grows like above.
+    fn color_at(&self, hit_record: &HitRecord) -> Color {
+        let scale = self.scale();
+        let mut transformed = hit_record.clone();
+        transformed.texture_coord = self.transform.apply(hit_record.texture_coord);
+        transformed.tangent = hit_record.tangent / scale;
+        transformed.bitangent = hit_record.bitangent / scale;
+        self.texture.color_at(&transformed)
+    }
+}
+
+// Changes the hit point before passing it to the texture. Used to move, stretch and rotate 3D textures like noise.
+// The point is scaled, then rotated around the axis and then translated.
+pub struct PositionTransform {
+    pub texture: Arc<dyn Texture>,
+    pub scale: Vec3,
+    pub rotation_axis: Vec3,
+    // In radians.
+    pub rotation_angle: f32,
+    pub translation: Vec3
+}
+
+impl PositionTransform {
+    pub fn new(texture: Arc<dyn Texture>, scale: Vec3, rotation_axis: Vec3, rotation_angle: f32, translation: Vec3) -> Self {
+        Self { texture, scale, rotation_axis: rotation_axis.normalized(), rotation_angle, translation }
+    }
+
+    pub fn apply(&self, p: Pt3) -> Pt3 {
+        self.rotate(self.scale * p) + self.translation
+    }
+
+    // Rodrigues' rotation formula.
+    fn rotate(&self, v: Vec3) -> Vec3 {
+        let (sin, cos) = self.rotation_angle.sin_cos();
+        let k = self.rotation_axis;
+        cos * v + sin * Vec3::cross(k, v) + (1.0 - cos) * Vec3::dot(k, v) * k
+    }
+}
+
+impl Texture for PositionTransform {
+    fn color(&self, uv: Vec2, hit_point: Pt3) -> Color {
+        self.color_filtered(uv, hit_point, 0.0)
+    }
+
+    fn color_filtered(&self, uv: Vec2, hit_point: Pt3, footprint: f32) -> Color {
+        self.texture.color_filtered(uv, self.apply(hit_point), footprint)
+    }
+
+    // Normals are scaled by the inverse of the scale to stay perpendicular to the stretched surface.
+    fn color_at(&self, hit_record: &HitRecord) -> Color {
+        let mut transformed = hit_record.clone();
+        transformed.point = self.apply(hit_record.point);
+        transformed.normal = self.rotate(hit_record.normal / self.scale).normalized();
+        transformed.cone_width = hit_record.cone_width * self.scale.x.abs().max(self.scale.y.abs()).max(self.scale.z.abs());
+        self.texture.color_at(&transformed)
+    }
+}
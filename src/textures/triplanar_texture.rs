@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use crate::{vec2::Vec2, vec3::{Color, Pt3}, hittable_objects::HitRecord};
+
+use super::Texture;
+
+// Maps a texture onto objects without texture coordinates. The texture is projected along each of the axes
+// and the projections are blended depending on how much the normal faces each axis.
+// The normal is only available when used directly by a material. Otherwise the projections are blended equally.
+pub struct TriplanarTexture {
+    pub texture: Arc<dyn Texture>,
+    // Number of repetitions of the texture per unit of distance.
+    pub scale: f32,
+    // Higher values make the transitions between the projections sharper.
+    pub sharpness: f32
+}
+
+impl TriplanarTexture {
+    pub fn new(texture: Arc<dyn Texture>, scale: f32, sharpness: f32) -> Self {
+        Self { texture, scale, sharpness }
+    }
+
+    fn blend(&self, weights: Color, hit_point: Pt3, footprint: f32) -> Color {
+        let p = self.scale * hit_point;
+        // Looking along the x axis the texture is on the zy plane and so on.
+        let projections = [Vec2::new(p.z, p.y), Vec2::new(p.x, p.z), Vec2::new(p.x, p.y)];
+        let total = weights.x + weights.y + weights.z;
+        let mut color = Color::all(0.0);
+        for (axis, uv) in projections.into_iter().enumerate() {
+            let weight = weights[axis] / total;
+            if weight > 1e-4 {
+                color += weight * self.texture.color_filtered(uv, hit_point, footprint);
+            }
+        }
+        color
+    }
+}
+
+impl Texture for TriplanarTexture {
+    fn color(&self, _: Vec2, hit_point: Pt3) -> Color {
+        self.blend(Color::all(1.0), hit_point, 0.0)
+    }
+
+    fn color_at(&self, hit_record: &HitRecord) -> Color {
+        let normal = hit_record.normal;
+        let weights = Color::new(
+            normal.x.abs().powf(self.sharpness),
+            normal.y.abs().powf(self.sharpness),
+            normal.z.abs().powf(self.sharpness));
+        self.blend(weights, hit_record.point, self.scale * hit_record.cone_width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ray::Ray, vec3::Vec3, materials::Lambertian};
+    use crate::textures::{SolidColor, ScaleTexture, ClampTexture, LerpTexture, UvTransformTexture, PositionTransform, UvTransform};
+
+    // Shows which projection was used.
+    struct UvColor;
+
+    impl Texture for UvColor {
+        fn color(&self, uv: Vec2, _: Pt3) -> Color {
+            Color::new(uv.x, uv.y, 0.0)
+        }
+    }
+
+    #[test]
+    fn combined_textures_see_the_normal() {
+        let point = Vec3::new(0.25, 0.5, 0.75);
+        let ray = Ray::new(Vec3::new(2.0, 0.5, 0.75), Vec3::new(-1.0, 0.0, 0.0));
+        let record = HitRecord::new(point, &ray, Vec3::new(1.0, 0.0, 0.0), 1.75, Vec2::all(0.0), Arc::new(Lambertian::from_color(Color::all(0.5))));
+        let triplanar: Arc<dyn Texture> = Arc::new(TriplanarTexture::new(Arc::new(UvColor), 1.0, 8.0));
+
+        // Facing along x only the projection on the zy plane is visible.
+        let expected = Color::new(0.75, 0.5, 0.0);
+        assert!((triplanar.color_at(&record) - expected).length() < 1e-3);
+        assert!((triplanar.color(Vec2::all(0.0), point) - expected).length() > 0.1);
+
+        let wrapped: Vec<Arc<dyn Texture>> = vec![
+            Arc::new(ScaleTexture::new(triplanar.clone(), Color::all(1.0))),
+            Arc::new(ClampTexture::new(triplanar.clone(), 0.0, 1.0)),
+            Arc::new(LerpTexture::new(triplanar.clone(), Arc::new(SolidColor::new(Color::all(1.0))), Arc::new(SolidColor::new(Color::all(0.0))))),
+            Arc::new(UvTransformTexture::new(triplanar.clone(), UvTransform::identity())),
+            Arc::new(PositionTransform::new(triplanar.clone(), Vec3::all(1.0), Vec3::new(0.0, 1.0, 0.0), 0.0, Vec3::all(0.0)))
+        ];
+        for texture in wrapped {
+            assert!((texture.color_at(&record) - expected).length() < 1e-3);
+        }
+    }
+}
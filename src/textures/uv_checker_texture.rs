@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use crate::{vec2::Vec2, vec3::{Color, Pt3}, hittable_objects::HitRecord};
+
+use super::Texture;
+
+// Checker pattern in texture coordinates so it stays attached to the surface when the object is transformed.
+pub struct UvCheckerTexture {
+    pub odd: Arc<dyn Texture>,
+    pub even: Arc<dyn Texture>,
+    // Number of squares along u and v.
+    pub squares: Vec2
+}
+
+impl UvCheckerTexture {
+    pub fn new(odd: Arc<dyn Texture>, even: Arc<dyn Texture>, squares: Vec2) -> Self {
+        Self { odd, even, squares }
+    }
+
+    fn is_odd(&self, uv: Vec2) -> bool {
+        let cell = (uv.x * self.squares.x).floor() as i64 + (uv.y * self.squares.y).floor() as i64;
+        cell.rem_euclid(2) == 1
+    }
+}
+
+impl Texture for UvCheckerTexture {
+    fn color(&self, uv: Vec2, hit_point: Pt3) -> Color {
+        self.color_filtered(uv, hit_point, 0.0)
+    }
+
+    fn color_filtered(&self, uv: Vec2, hit_point: Pt3, footprint: f32) -> Color {
+        if self.is_odd(uv) {
+            self.odd.color_filtered(uv, hit_point, footprint)
+        } else {
+            self.even.color_filtered(uv, hit_point, footprint)
+        }
+    }
+
+    fn color_at(&self, hit_record: &HitRecord) -> Color {
+        if self.is_odd(hit_record.texture_coord) {
+            self.odd.color_at(hit_record)
+        } else {
+            self.even.color_at(hit_record)
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use crate::{perlin::{Perlin, Turbulence}, vec2::Vec2, vec3::{Vec3, Color}};
+
+use super::Texture;
+
+pub struct WoodTexture {
+    pub perlin: Perlin,
+    pub turbulence: Turbulence,
+    // Number of rings per unit of distance.
+    pub frequency: f32,
+    // How much the turbulence bends the rings.
+    pub distortion: f32,
+    pub light_color: Color,
+    pub dark_color: Color
+}
+
+impl WoodTexture {
+    pub fn new(frequency: f32, light_color: Color, dark_color: Color) -> Self {
+        Self { perlin: Perlin::new(), turbulence: Turbulence::default(), frequency, distortion: 0.1, light_color, dark_color }
+    }
+}
+
+impl Texture for WoodTexture {
+    fn color(&self, _: Vec2, hit_point: Vec3) -> Vec3 {
+        // Growth rings are circles around the y axis.
+        let distance = f32::sqrt(hit_point.x * hit_point.x + hit_point.z * hit_point.z)
+            + self.distortion * self.perlin.fbm(hit_point, self.turbulence);
+        // The turbulence can make the distance negative near the center. Fract would mirror the rings there.
+        let ring = (self.frequency * distance).rem_euclid(1.0);
+        // Each ring slowly gets darker and then sharply changes back to light.
+        let t = ring * ring * (3.0 - 2.0 * ring);
+        (1.0 - t) * self.light_color + t * self.dark_color
+    }
+}
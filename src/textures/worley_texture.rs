@@ -0,0 +1,40 @@
+use crate::{worley::{Worley, DistanceMetric}, vec2::Vec2, vec3::Vec3};
+
+use super::{Texture, ColorRamp};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WorleyOutput {
+    // Distance to the closest feature point. Looks like cells or scales.
+    F1,
+    // Distance to the second closest feature point.
+    F2,
+    // Close to 0 near the borders between cells. Good for cracks and stone walls.
+    F2MinusF1
+}
+
+pub struct WorleyTexture {
+    pub worley: Worley,
+    // Number of cells per unit of distance.
+    pub frequency: f32,
+    pub output: WorleyOutput,
+    pub metric: DistanceMetric,
+    pub ramp: ColorRamp
+}
+
+impl WorleyTexture {
+    pub fn new(frequency: f32, output: WorleyOutput, metric: DistanceMetric, ramp: ColorRamp) -> Self {
+        Self { worley: Worley::new(), frequency, output, metric, ramp }
+    }
+}
+
+impl Texture for WorleyTexture {
+    fn color(&self, _: Vec2, hit_point: Vec3) -> Vec3 {
+        let (f1, f2) = self.worley.distances(self.frequency * hit_point, self.metric);
+        let value = match self.output {
+            WorleyOutput::F1 => f1,
+            WorleyOutput::F2 => f2,
+            WorleyOutput::F2MinusF1 => f2 - f1
+        };
+        self.ramp.color(value)
+    }
+}
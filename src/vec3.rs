@@ -57,6 +57,14 @@ impl Vec3 {
         }
     }
 
+    // Random direction around the z axis with the probability proportional to the cosine of the angle with the z axis.
+    pub fn random_cosine_direction() -> Self {
+        let (r1, r2): (f32, f32) = (rand::thread_rng().gen(), rand::thread_rng().gen());
+        let phi = std::f32::consts::TAU * r1;
+        let r = r2.sqrt();
+        Self::new(phi.cos() * r, phi.sin() * r, f32::sqrt(1.0 - r2))
+    }
+
     pub fn random_unit() -> Self {
         loop {
             let vector = Self::random_in_unit_sphere().normalized();
@@ -108,7 +116,7 @@ impl Vec3 {
 
     pub fn refract(self, normal: Vec3, refraction_ratio: f32) -> Vec3 {
         let cos_theta = Vec3::dot(-self, normal).min(1.0);
-        let perpendicular = refraction_ratio * (self * cos_theta * normal);
+        let perpendicular = refraction_ratio * (self + cos_theta * normal);
         let parallel = -(1.0 - perpendicular.length_squared()).abs().sqrt() * normal;
         perpendicular + parallel
     }
@@ -164,6 +172,13 @@ impl std::ops::Div<f32> for Vec3 {
     }
 }
 
+impl std::ops::Div<Vec3> for Vec3 {
+    type Output = Self;
+    fn div(self, rhs: Vec3) -> Self {
+        Self{ x: self.x / rhs.x, y: self.y / rhs.y, z: self.z / rhs.z }
+    }
+}
+
 impl std::ops::DivAssign<f32> for Vec3 {
     fn div_assign(&mut self, rhs: f32) {
         self.x /= rhs;
@@ -200,4 +215,23 @@ impl std::ops::IndexMut<usize> for Vec3 {
             _ => panic!("index out of range")
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refraction_follows_snells_law() {
+        let normal = Vec3::new(0.0, 1.0, 0.0);
+        let incoming = Vec3::new(1.0, -1.0, 0.0).normalized();
+        // Going from air into glass bends the ray towards the normal.
+        let refracted = incoming.refract(normal, 1.0 / 1.5);
+        assert!((refracted.length() - 1.0).abs() < 1e-5);
+        assert!((refracted.x - incoming.x / 1.5).abs() < 1e-5);
+        assert!(refracted.y < 0.0 && refracted.z.abs() < 1e-6);
+
+        // Without a change of the index of refraction the ray goes straight through.
+        assert!((incoming.refract(normal, 1.0) - incoming).length() < 1e-5);
+    }
+}
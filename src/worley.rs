@@ -0,0 +1,146 @@
+use rand::Rng;
+
+use crate::vec3::Vec3;
+
+const POINT_COUNT: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DistanceMetric {
+    Euclidean,
+    // Sum of the distances along the axes. Creates diamond shaped cells.
+    Manhattan,
+    // Largest of the distances along the axes. Creates square cells.
+    Chebyshev
+}
+
+impl DistanceMetric {
+    pub fn distance(&self, a: Vec3, b: Vec3) -> f32 {
+        let d = a - b;
+        match self {
+            DistanceMetric::Euclidean => d.length(),
+            DistanceMetric::Manhattan => d.x.abs() + d.y.abs() + d.z.abs(),
+            DistanceMetric::Chebyshev => d.x.abs().max(d.y.abs()).max(d.z.abs())
+        }
+    }
+}
+
+// Cellular noise. Space is divided into cubes each containing a single random feature point
+// and the noise is the distance to the nearest feature points.
+pub struct Worley {
+    offsets: [Vec3; POINT_COUNT],
+    permutations: [usize; POINT_COUNT]
+}
+
+impl Worley {
+    pub fn new() -> Self {
+        let mut offsets = [Vec3::all(0.0); POINT_COUNT];
+        for item in &mut offsets {
+            *item = Vec3::new_random();
+        }
+
+        let mut permutations = [0; POINT_COUNT];
+        for (i, item) in permutations.iter_mut().enumerate() {
+            *item = i;
+        }
+        for i in (1..permutations.len()).rev() {
+            let target = rand::thread_rng().gen_range(0..=i);
+            permutations.swap(i, target);
+        }
+        Self { offsets, permutations }
+    }
+
+    fn feature_point(&self, i: i32, j: i32, k: i32) -> Vec3 {
+        let index = |n: i32| (n & (POINT_COUNT as i32 - 1)) as usize;
+        let hash = self.permutations[index(self.permutations[index(self.permutations[index(i)] as i32 + j)] as i32 + k)];
+        Vec3::new(i as f32, j as f32, k as f32) + self.offsets[hash]
+    }
+
+    // Returns the distances to the closest and the second closest feature point.
+    pub fn distances(&self, p: Vec3, metric: DistanceMetric) -> (f32, f32) {
+        let cell = [p.x.floor() as i32, p.y.floor() as i32, p.z.floor() as i32];
+        let (mut f1, mut f2) = (f32::INFINITY, f32::INFINITY);
+        // The neighbouring cells always contain the closest point but the second closest can be further away, for example
+        // when the point is in a corner of its cell. So the cells are searched in growing shells around the cell of the point.
+        // Feature points are inside of their cells so a cell in shell r is at least r - 1 away along one of the axes,
+        // which no metric is shorter than, and the search can stop when that is further than the second distance.
+        let mut radius: i32 = 0;
+        while ((radius - 1) as f32) < f2 {
+            for di in -radius..=radius {
+                for dj in -radius..=radius {
+                    for dk in -radius..=radius {
+                        if di.abs().max(dj.abs()).max(dk.abs()) != radius {
+                            continue;
+                        }
+                        let (i, j, k) = (cell[0] + di, cell[1] + dj, cell[2] + dk);
+                        // Skips cells that can't contain anything closer than the second closest point so far.
+                        let gap = |start: i32, coordinate: f32| f32::max(0.0, f32::max(start as f32 - coordinate, coordinate - (start + 1) as f32));
+                        if metric.distance(Vec3::new(gap(i, p.x), gap(j, p.y), gap(k, p.z)), Vec3::all(0.0)) >= f2 {
+                            continue;
+                        }
+
+                        let distance = metric.distance(p, self.feature_point(i, j, k));
+                        if distance < f1 {
+                            f2 = f1;
+                            f1 = distance;
+                        } else if distance < f2 {
+                            f2 = distance;
+                        }
+                    }
+                }
+            }
+            radius += 1;
+        }
+        (f1, f2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_point() -> Vec3 {
+        let mut rng = rand::thread_rng();
+        Vec3::new(rng.gen_range(-20.0..20.0), rng.gen_range(-20.0..20.0), rng.gen_range(-20.0..20.0))
+    }
+
+    // Checks every feature point in a large area around the point.
+    fn brute_force(worley: &Worley, p: Vec3, metric: DistanceMetric) -> (f32, f32) {
+        let mut distances = Vec::new();
+        let (i, j, k) = (p.x.floor() as i32, p.y.floor() as i32, p.z.floor() as i32);
+        for di in -5..=5 {
+            for dj in -5..=5 {
+                for dk in -5..=5 {
+                    distances.push(metric.distance(p, worley.feature_point(i + di, j + dj, k + dk)));
+                }
+            }
+        }
+        distances.sort_by(f32::total_cmp);
+        (distances[0], distances[1])
+    }
+
+    #[test]
+    fn finds_the_two_closest_points() {
+        let worley = Worley::new();
+        for metric in [DistanceMetric::Euclidean, DistanceMetric::Manhattan, DistanceMetric::Chebyshev] {
+            for _ in 0..300 {
+                let p = random_point();
+                let (f1, f2) = worley.distances(p, metric);
+                assert_eq!((f1, f2), brute_force(&worley, p, metric), "{:?} at {:?}", metric, p);
+                assert!(f1 <= f2);
+            }
+        }
+    }
+
+    #[test]
+    fn zero_at_feature_points() {
+        let worley = Worley::new();
+        for i in -3..3 {
+            let feature = worley.feature_point(i, 2 * i, -i);
+            let (f1, f2) = worley.distances(feature, DistanceMetric::Euclidean);
+            assert_eq!(f1, 0.0);
+            assert!(f2 > 0.0);
+            // The same point always gives the same distances.
+            assert_eq!(worley.distances(feature + Vec3::all(0.1), DistanceMetric::Euclidean), worley.distances(feature + Vec3::all(0.1), DistanceMetric::Euclidean));
+        }
+    }
+}